@@ -0,0 +1,156 @@
+//! Move generation, win checking, random playouts and AI search speed for the main boards.
+//! Run with `cargo bench`; criterion keeps earlier results in `target/criterion`
+//! and reports the change against them.
+
+#![allow(clippy::needless_return)]
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use tictactoe::{
+    ai::{mcts::{Budget, Mcts}, negamax},
+    board::{Board, EndGame},
+    cubegame::{cubeboard::CubeBoard, qubicboard::QubicBoard},
+    game::Game,
+    mnkboard::MnkBoard,
+    ultiboard::UltimateBoard,
+    utils::Piece,
+};
+
+/// Delegates to `G`, counting every move applied, so searches can be measured in nodes.
+#[derive(Clone)]
+struct Counted<G> {
+    game: G,
+    nodes: Rc<Cell<u64>>,
+}
+
+impl<G: Game> Game for Counted<G> {
+    type Move = G::Move;
+    type Error = G::Error;
+
+    fn side_to_move(&self) -> Piece { self.game.side_to_move() }
+
+    fn legal_moves(&self) -> Vec<G::Move> { self.game.legal_moves() }
+
+    fn apply(&mut self, mv: G::Move) -> Result<(), G::Error> {
+        self.nodes.set(self.nodes.get() + 1);
+        return self.game.apply(mv);
+    }
+
+    fn undo(&mut self) -> Option<G::Move> { self.game.undo() }
+
+    fn redo(&mut self) -> Option<G::Move> { self.game.redo() }
+
+    fn history(&self) -> Vec<G::Move> { self.game.history() }
+
+    fn outcome(&self) -> EndGame { self.game.outcome() }
+
+    fn winning_lines(&self) -> Vec<Vec<G::Move>> { self.game.winning_lines() }
+}
+
+/// `game` after `moves` random moves, or fewer if it ends first.
+fn position<G: Game>(mut game: G, moves: usize, seed: u64) -> G {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..moves {
+        let Some(&mv) = game.legal_moves().choose(&mut rng) else { break; };
+        game.apply(mv).unwrap();
+    }
+
+    return game;
+}
+
+fn playout<G: Game>(mut game: G, rng: &mut StdRng) -> EndGame {
+    while let Some(&mv) = game.legal_moves().choose(rng) {
+        game.apply(mv).unwrap();
+    }
+
+    return game.outcome();
+}
+
+fn legal_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("legal_moves");
+
+    let board = position(Board::new(), 4, 1);
+    group.bench_function("normal", |b| b.iter(|| Game::legal_moves(black_box(&board))));
+
+    let ultimate = position(UltimateBoard::new(), 30, 1);
+    group.bench_function("ultimate", |b| b.iter(|| Game::legal_moves(black_box(&ultimate))));
+
+    let cube = position(CubeBoard::new(), 6, 1);
+    group.bench_function("cube", |b| b.iter(|| Game::legal_moves(black_box(&cube))));
+
+    let qubic = position(QubicBoard::new(), 20, 1);
+    group.bench_function("qubic", |b| b.iter(|| Game::legal_moves(black_box(&qubic))));
+
+    let gomoku = position(MnkBoard::new(15, 15, 5).unwrap(), 40, 1);
+    group.bench_function("gomoku", |b| b.iter(|| Game::legal_moves(black_box(&gomoku))));
+
+    group.finish();
+}
+
+fn win_check(c: &mut Criterion) {
+    let mut group = c.benchmark_group("win_check");
+
+    let board = position(Board::new(), 4, 1);
+    group.bench_function("normal", |b| b.iter(|| black_box(&board).win_check()));
+
+    let mut ultimate = position(UltimateBoard::new(), 30, 1);
+    group.bench_function("ultimate", |b| b.iter(|| black_box(&mut ultimate).win_check()));
+
+    let cube = position(CubeBoard::new(), 6, 1);
+    group.bench_function("cube", |b| b.iter(|| black_box(&cube).win_check()));
+
+    group.finish();
+}
+
+fn playouts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("playout");
+    group.throughput(Throughput::Elements(1));
+    let mut rng = StdRng::seed_from_u64(2);
+
+    group.bench_function("normal", |b| b.iter(|| playout(Board::new(), &mut rng)));
+    group.bench_function("ultimate", |b| b.iter(|| playout(UltimateBoard::new(), &mut rng)));
+    group.bench_function("cube", |b| b.iter(|| playout(CubeBoard::new(), &mut rng)));
+
+    group.finish();
+}
+
+fn search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search");
+    group.sample_size(10);
+
+    let nodes = Rc::new(Cell::new(0));
+    let counted = Counted { game: Board::new(), nodes: nodes.clone() };
+    negamax::best_move(&counted, negamax::FULL_DEPTH);
+    group.throughput(Throughput::Elements(nodes.get()));
+    group.bench_function(BenchmarkId::new("negamax", "normal"), |b| {
+        b.iter(|| negamax::best_move(black_box(&counted), negamax::FULL_DEPTH))
+    });
+
+    let nodes = Rc::new(Cell::new(0));
+    let counted = Counted { game: position(UltimateBoard::new(), 10, 3), nodes: nodes.clone() };
+    negamax::best_move(&counted, 4);
+    group.throughput(Throughput::Elements(nodes.get()));
+    group.bench_function(BenchmarkId::new("negamax", "ultimate"), |b| {
+        b.iter(|| negamax::best_move(black_box(&counted), 4))
+    });
+
+    let iterations = 1000;
+    let engine = Mcts::new(Budget::Iterations(iterations));
+    let mut rng = StdRng::seed_from_u64(4);
+    group.throughput(Throughput::Elements(iterations as u64));
+
+    let ultimate = position(UltimateBoard::new(), 10, 3);
+    group.bench_function(BenchmarkId::new("mcts", "ultimate"), |b| b.iter(|| engine.search(black_box(&ultimate), &mut rng)));
+
+    let cube = position(CubeBoard::new(), 2, 3);
+    group.bench_function(BenchmarkId::new("mcts", "cube"), |b| b.iter(|| engine.search(black_box(&cube), &mut rng)));
+
+    group.finish();
+}
+
+criterion_group!(benches, legal_moves, win_check, playouts, search);
+criterion_main!(benches);
@@ -0,0 +1,4 @@
+pub mod baseline;
+pub mod mcts;
+pub mod negamax;
+pub mod solver;
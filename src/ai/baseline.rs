@@ -0,0 +1,75 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::board::EndGame;
+use crate::game::Game;
+
+// Functions
+
+/// Any legal move, or `None` if the game is over.
+pub fn random_move<G: Game, R: Rng>(game: &G, rng: &mut R) -> Option<G::Move> {
+    return game.legal_moves().choose(rng).copied();
+}
+
+/// Wins on the spot if it can, otherwise plays a random move that neither
+/// loses on the spot, as in misère, nor lets the opponent win on their next
+/// move, if there is one.
+pub fn greedy_move<G: Game, R: Rng>(game: &G, rng: &mut R) -> Option<G::Move> {
+    let side = game.side_to_move();
+    let mut moves = game.legal_moves();
+    moves.shuffle(rng);
+
+    let mut safe = None;
+    for &mv in &moves {
+        let mut next = game.clone();
+        next.apply(mv).unwrap();
+
+        if next.outcome() == EndGame::Winner(side) { return Some(mv); }
+        if safe.is_none() && next.outcome() != EndGame::Winner(side.opponent()) && !gives_a_win(&next) { safe = Some(mv); }
+    }
+
+    return safe.or(moves.first().copied());
+}
+
+/// Whether the side to move can win with their next move.
+fn gives_a_win<G: Game>(game: &G) -> bool {
+    let side = game.side_to_move();
+
+    return game.legal_moves().into_iter().any(|mv| {
+        let mut next = game.clone();
+        next.apply(mv).unwrap();
+        next.outcome() == EndGame::Winner(side)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn greedy_wins_then_blocks() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // X to move with a1 a2 against b1 b2: a3 wins
+        let mut board = Board::new();
+        for mv in [0, 3, 1, 4] { board.apply(mv).unwrap(); }
+        assert_eq!(greedy_move(&board, &mut rng), Some(2));
+
+        // O to move after X's a1 a2: a3 is the only block
+        let mut board = Board::new();
+        for mv in [0, 4, 1] { board.apply(mv).unwrap(); }
+        for _ in 0..10 {
+            assert_eq!(greedy_move(&board, &mut rng), Some(2));
+        }
+
+        // In misère the same a3 completes X's line and loses
+        let mut board = Board::new();
+        board.set_misere(true);
+        for mv in [0, 3, 1, 4] { board.apply(mv).unwrap(); }
+        for _ in 0..10 {
+            assert_ne!(greedy_move(&board, &mut rng), Some(2));
+        }
+    }
+}
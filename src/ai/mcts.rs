@@ -0,0 +1,217 @@
+use std::time::{Duration, Instant};
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::board::EndGame;
+use crate::game::Game;
+use crate::utils::Piece;
+
+// Type Definitions
+
+/// How long the search is allowed to run.
+#[derive(Debug, Clone, Copy)]
+pub enum Budget {
+    Iterations(u32),
+    Time(Duration),
+}
+
+/// Monte Carlo Tree Search using UCT to pick which branch to explore.
+#[derive(Debug, Clone)]
+pub struct Mcts {
+    pub budget: Budget,
+    pub exploration: f64,
+}
+
+struct Node<M> {
+    mv: Option<M>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<M>,
+    /// The piece that made `mv`, whose point of view `wins` is counted from.
+    player: Piece,
+    visits: u32,
+    wins: f64,
+}
+
+// Type Implementations
+
+impl Default for Mcts {
+    fn default() -> Self { Self::new(Budget::Time(Duration::from_secs(1))) }
+}
+
+impl Mcts {
+    pub fn new(budget: Budget) -> Self {
+        Self {
+            budget,
+            exploration: std::f64::consts::SQRT_2,
+        }
+    }
+
+    /// Searches from `game` and returns the most visited move, or `None` if the
+    /// game is already over.
+    pub fn best_move<G: Game>(&self, game: &G) -> Option<G::Move> {
+        return self.search(game, &mut rand::thread_rng());
+    }
+
+    pub fn search<G: Game, R: Rng>(&self, game: &G, rng: &mut R) -> Option<G::Move> {
+        let mut moves = game.legal_moves();
+        if moves.len() <= 1 { return moves.pop(); }
+
+        let tree = self.grow(game, rng);
+
+        return tree[0].children.iter()
+            .max_by_key(|&&child| tree[child].visits)
+            .and_then(|&child| tree[child].mv);
+    }
+
+    /// The share of playouts from `game` the side to move won, counting a
+    /// draw as half. 0.5 if the budget ran out before the first playout.
+    pub fn win_rate<G: Game, R: Rng>(&self, game: &G, rng: &mut R) -> f64 {
+        let tree = self.grow(game, rng);
+        if tree[0].visits == 0 { return 0.5; }
+
+        // The root is counted for the side that moved into `game`
+        return 1.0 - tree[0].wins / tree[0].visits as f64;
+    }
+
+    /// Runs the search from `game` until the budget is spent.
+    fn grow<G: Game, R: Rng>(&self, game: &G, rng: &mut R) -> Vec<Node<G::Move>> {
+        let mut tree = vec![Node {
+            mv: None,
+            parent: None,
+            children: Vec::new(),
+            untried: game.legal_moves(),
+            player: game.side_to_move().opponent(),
+            visits: 0,
+            wins: 0.0,
+        }];
+
+        let start = Instant::now();
+        let mut iterations = 0;
+
+        while !self.out_of_budget(iterations, start) {
+            self.iterate(&mut tree, game.clone(), rng);
+            iterations += 1;
+        }
+
+        return tree;
+    }
+
+    fn out_of_budget(&self, iterations: u32, start: Instant) -> bool {
+        return match self.budget {
+            Budget::Iterations(limit) => iterations >= limit,
+            Budget::Time(limit) => start.elapsed() >= limit,
+        };
+    }
+
+    fn iterate<G: Game, R: Rng>(&self, tree: &mut Vec<Node<G::Move>>, mut game: G, rng: &mut R) {
+        // Selection
+        let mut node = 0;
+        while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+            node = self.select_child(tree, node);
+            game.apply(tree[node].mv.unwrap()).unwrap();
+        }
+
+        // Expansion
+        if !tree[node].untried.is_empty() {
+            let index = rng.gen_range(0..tree[node].untried.len());
+            let mv = tree[node].untried.swap_remove(index);
+            let player = game.side_to_move();
+            game.apply(mv).unwrap();
+
+            tree.push(Node {
+                mv: Some(mv),
+                parent: Some(node),
+                children: Vec::new(),
+                untried: game.legal_moves(),
+                player,
+                visits: 0,
+                wins: 0.0,
+            });
+            let child = tree.len() - 1;
+            tree[node].children.push(child);
+            node = child;
+        }
+
+        // Simulation
+        while let Some(&mv) = game.legal_moves().choose(rng) {
+            game.apply(mv).unwrap();
+        }
+        let outcome = game.outcome();
+
+        // Backpropagation
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let node = &mut tree[index];
+            node.visits += 1;
+            node.wins += match outcome {
+                EndGame::Winner(winner) if winner == node.player => 1.0,
+                EndGame::Winner(_) => 0.0,
+                _ => 0.5,
+            };
+            current = node.parent;
+        }
+    }
+
+    fn select_child<M>(&self, tree: &[Node<M>], node: usize) -> usize {
+        let parent_visits = (tree[node].visits as f64).ln();
+
+        let uct = |child: usize| {
+            let child = &tree[child];
+            let visits = child.visits as f64;
+            child.wins / visits + self.exploration * (parent_visits / visits).sqrt()
+        };
+
+        return *tree[node].children.iter()
+            .max_by(|&&a, &&b| uct(a).total_cmp(&uct(b)))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::ultiboard::UltimateBoard;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn takes_the_win() {
+        let mut board = Board::new();
+        for mv in [0, 3, 1, 4] {
+            board.apply(mv).unwrap();
+        }
+
+        let mcts = Mcts::new(Budget::Iterations(2000));
+        assert_eq!(mcts.search(&board, &mut StdRng::seed_from_u64(1)), Some(2));
+    }
+
+    #[test]
+    fn sees_who_is_ahead() {
+        let mut board = Board::new();
+        for mv in [0, 3, 1, 4] {
+            board.apply(mv).unwrap();
+        }
+
+        let mcts = Mcts::new(Budget::Iterations(2000));
+        assert!(mcts.win_rate(&board, &mut StdRng::seed_from_u64(1)) > 0.8);
+
+        // O to move with X threatening a3, which most playouts fail to block
+        let mut board = Board::new();
+        for mv in [0, 3, 1, 4, 5] {
+            board.apply(mv).unwrap();
+        }
+        assert!(mcts.win_rate(&board, &mut StdRng::seed_from_u64(1)) < 0.5);
+    }
+
+    #[test]
+    fn respects_the_focused_board() {
+        let mut board = UltimateBoard::new();
+        board.apply((4, 7)).unwrap();
+
+        let mcts = Mcts::new(Budget::Iterations(200));
+        let (b, _) = mcts.search(&board, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(b, 7);
+    }
+}
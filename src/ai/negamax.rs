@@ -0,0 +1,159 @@
+use std::time::Instant;
+
+use crate::board::EndGame;
+use crate::game::Game;
+
+// Constants
+
+/// Score of a won position, before the bonus for winning sooner.
+pub const WIN_SCORE: i32 = 1000;
+/// Deep enough to search any classic 3x3 game to the end.
+pub const FULL_DEPTH: usize = 9;
+
+// Type Definitions
+
+/// Limits on a search, and what it found out about its own result.
+#[derive(Debug, Clone, Copy)]
+pub struct Search {
+    /// Give up once this passes, leaving `aborted` set.
+    pub deadline: Option<Instant>,
+    /// No line was cut short by the depth limit, so a score of 0 is a proven draw.
+    pub exact: bool,
+    /// The deadline passed mid-search, so the scores can't be trusted.
+    pub aborted: bool,
+}
+
+// Type Implementations
+
+impl Search {
+    pub fn new(deadline: Option<Instant>) -> Self {
+        Self {
+            deadline,
+            exact: true,
+            aborted: false,
+        }
+    }
+}
+
+// Functions
+
+/// Scores the position from the point of view of the side to move, searching
+/// at most `depth` plies. Positive is winning, negative is losing, 0 is a draw
+/// or unknown past the horizon.
+pub fn negamax<G: Game>(game: &mut G, depth: usize, mut alpha: i32, beta: i32, search: &mut Search) -> i32 {
+    match game.outcome() {
+        EndGame::Winner(piece) => {
+            // Prefer quick wins and slow losses
+            let score = WIN_SCORE + depth as i32;
+            return if piece == game.side_to_move() { score } else { -score };
+        },
+        EndGame::CatsGame => { return 0; },
+        EndGame::NotDone => { },
+    }
+
+    if depth == 0 {
+        search.exact = false;
+        return 0;
+    }
+    if search.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        search.aborted = true;
+        return 0;
+    }
+
+    let mut best = -i32::MAX;
+
+    for mv in game.legal_moves() {
+        game.apply(mv).unwrap();
+        let score = -negamax(game, depth - 1, -beta, -alpha, search);
+        game.undo();
+
+        if score > best { best = score; }
+        if best > alpha { alpha = best; }
+        if alpha >= beta { break; }
+    }
+
+    return best;
+}
+
+/// Finds the best move for the side to move along with its score, or `None`
+/// if the game is already over.
+pub fn best_move<G: Game>(game: &G, depth: usize) -> Option<(G::Move, i32)> {
+    return best_move_within(game, depth, &mut Search::new(None));
+}
+
+/// `best_move` within the limits of `search`, which records whether the
+/// result is exact or was cut off.
+pub fn best_move_within<G: Game>(game: &G, depth: usize, search: &mut Search) -> Option<(G::Move, i32)> {
+    let mut game = game.clone();
+    let mut best: Option<(G::Move, i32)> = None;
+    let mut alpha = -i32::MAX;
+
+    for mv in game.legal_moves() {
+        game.apply(mv).unwrap();
+        let score = -negamax(&mut game, depth.saturating_sub(1), -i32::MAX, -alpha, search);
+        game.undo();
+
+        if best.is_none() || score > alpha {
+            best = Some((mv, score));
+            alpha = score;
+        }
+    }
+
+    return best;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::utils::Piece;
+
+    #[test]
+    fn perfect_play_is_a_draw() {
+        let mut board = Board::new();
+
+        while let Some((mv, _)) = best_move(&board, FULL_DEPTH) {
+            board.apply(mv).unwrap();
+        }
+
+        assert_eq!(board.outcome(), EndGame::CatsGame);
+    }
+
+    #[test]
+    fn takes_the_win() {
+        let mut board = Board::new();
+        for mv in [0, 3, 1, 4] {
+            board.apply(mv).unwrap();
+        }
+
+        let (mv, score) = best_move(&board, FULL_DEPTH).unwrap();
+        assert_eq!(mv, 2);
+        assert!(score > WIN_SCORE);
+    }
+
+    #[test]
+    fn knows_when_it_saw_the_end() {
+        let mut shallow = Search::new(None);
+        best_move_within(&Board::new(), 2, &mut shallow);
+        assert!(!shallow.exact);
+
+        let mut full = Search::new(None);
+        best_move_within(&Board::new(), FULL_DEPTH, &mut full);
+        assert!(full.exact && !full.aborted);
+    }
+
+    #[test]
+    fn avoids_lines_in_misere() {
+        let mut board = Board::new();
+        board.set_misere(true);
+        for mv in [0, 3, 1, 4] {
+            board.apply(mv).unwrap();
+        }
+
+        let (mv, _) = best_move(&board, FULL_DEPTH).unwrap();
+        assert_ne!(mv, 2);
+
+        board.apply(2).unwrap();
+        assert_eq!(board.outcome(), EndGame::Winner(Piece::O));
+    }
+}
@@ -0,0 +1,284 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::ai::mcts::{Budget, Mcts};
+use crate::ai::negamax::{self, Search, WIN_SCORE};
+use crate::board::EndGame;
+use crate::game::Game;
+use crate::notation::Notation;
+use crate::utils::Piece;
+
+// Constants
+
+/// How long a hint may think before settling for the deepest search it finished.
+pub const HINT_TIME: Duration = Duration::from_secs(1);
+
+/// How far a move has to drop the estimated chances of an unclear position to
+/// be called dubious.
+pub const DUBIOUS_DROP: f64 = 0.25;
+
+// Type Definitions
+
+/// How the game goes for the side to move with best play from both sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Winning,
+    Drawing,
+    Losing,
+    /// The search ran out of time before finding a forced result.
+    Unclear,
+}
+
+/// How much a played move gave away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quality {
+    /// Kept the verdict the position already had.
+    Best,
+    /// Let a win slip to a draw.
+    Inaccuracy,
+    /// Turned a win or a draw into a loss.
+    Blunder,
+    /// Nothing is proven, but the chances MCTS gives dropped by `DUBIOUS_DROP`
+    /// or more.
+    Dubious,
+}
+
+/// A move from a finished game, with the verdict and the chances for the side
+/// that made it before and after.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Annotation<M> {
+    pub mv: M,
+    pub side: Piece,
+    pub before: Verdict,
+    pub after: Verdict,
+    /// 1 for a win, 0.5 for a draw and 0 for a loss, estimated by MCTS when
+    /// the verdict is unclear.
+    pub chances_before: f64,
+    pub chances_after: f64,
+}
+
+// Type Implementations
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Winning => write!(f, "winning"),
+            Verdict::Drawing => write!(f, "drawing"),
+            Verdict::Losing => write!(f, "losing"),
+            Verdict::Unclear => write!(f, "unclear"),
+        }
+    }
+}
+
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Quality::Best => write!(f, "best"),
+            Quality::Inaccuracy => write!(f, "inaccuracy"),
+            Quality::Blunder => write!(f, "blunder"),
+            Quality::Dubious => write!(f, "dubious"),
+        }
+    }
+}
+
+impl Verdict {
+    /// The verdict for a negamax score. Only a score of 0 can come from the
+    /// depth limit, so wins and losses are always proven.
+    fn from_score(score: i32, exact: bool) -> Self {
+        return match score {
+            score if score >= WIN_SCORE => Verdict::Winning,
+            score if score <= -WIN_SCORE => Verdict::Losing,
+            _ if exact => Verdict::Drawing,
+            _ => Verdict::Unclear,
+        };
+    }
+
+    /// The same position seen by the other side.
+    fn opponent(&self) -> Self {
+        return match self {
+            Verdict::Winning => Verdict::Losing,
+            Verdict::Losing => Verdict::Winning,
+            verdict => *verdict,
+        };
+    }
+
+    /// The chances of a proven verdict, `None` when unclear.
+    fn chances(&self) -> Option<f64> {
+        return self.rank().map(|rank| rank as f64 / 2.0);
+    }
+
+    /// Higher is better, `None` when unclear.
+    fn rank(&self) -> Option<u8> {
+        return match self {
+            Verdict::Winning => Some(2),
+            Verdict::Drawing => Some(1),
+            Verdict::Losing => Some(0),
+            Verdict::Unclear => None,
+        };
+    }
+}
+
+impl<M> Annotation<M> {
+    /// Compares the verdicts, or the estimated chances if either is unclear.
+    /// `None` when nothing is proven and the chances held up.
+    pub fn quality(&self) -> Option<Quality> {
+        let (Some(before), Some(after)) = (self.before.rank(), self.after.rank()) else {
+            return (self.chances_before - self.chances_after >= DUBIOUS_DROP).then_some(Quality::Dubious);
+        };
+
+        return Some(if after >= before {
+            Quality::Best
+        } else if self.after == Verdict::Losing {
+            Quality::Blunder
+        } else {
+            Quality::Inaccuracy
+        });
+    }
+}
+
+// Functions
+
+/// Searches deeper and deeper until the result is known or `time` runs out,
+/// returning the best move found along with the verdict for the side to move.
+/// `None` if the game is already over.
+pub fn solve<G: Game>(game: &G, time: Duration) -> Option<(G::Move, Verdict)> {
+    // Too long to fit in an Instant means no deadline at all
+    let deadline = Instant::now().checked_add(time);
+    let mut best = None;
+
+    for depth in 1.. {
+        // The first pass always finishes, so there's a move to suggest
+        let mut search = Search::new(if depth > 1 { deadline } else { None });
+        let found = negamax::best_move_within(game, depth, &mut search);
+        if search.aborted { break; }
+
+        let (mv, score) = found?;
+        let verdict = Verdict::from_score(score, search.exact);
+        best = Some((mv, verdict));
+
+        if verdict != Verdict::Unclear || deadline.is_some_and(|deadline| Instant::now() >= deadline) { break; }
+    }
+
+    return best;
+}
+
+/// The verdict for the side to move, searching for at most `time`.
+pub fn verdict<G: Game>(game: &G, time: Duration) -> Verdict {
+    return match game.outcome() {
+        // In misère the player who just moved can have lost
+        EndGame::Winner(piece) if piece == game.side_to_move() => Verdict::Winning,
+        EndGame::Winner(_) => Verdict::Losing,
+        EndGame::CatsGame => Verdict::Drawing,
+        EndGame::NotDone => solve(game, time).map_or(Verdict::Unclear, |(_, verdict)| verdict),
+    };
+}
+
+/// The verdict for the side to move along with their chances, which MCTS
+/// estimates for another `time` when the verdict is unclear.
+pub fn judge<G: Game>(game: &G, time: Duration) -> (Verdict, f64) {
+    let verdict = verdict(game, time);
+    let chances = verdict.chances()
+        .unwrap_or_else(|| Mcts::new(Budget::Time(time)).win_rate(game, &mut rand::thread_rng()));
+
+    return (verdict, chances);
+}
+
+/// Judges every move played in `game` from the start, spending at most twice
+/// `time` on each position.
+pub fn analyze<G: Game>(game: &G, time: Duration) -> Vec<Annotation<G::Move>> {
+    let moves = game.history();
+    let mut game = game.clone();
+    while game.undo().is_some() { }
+
+    let mut annotations = Vec::new();
+    let (mut before, mut chances_before) = judge(&game, time);
+
+    for mv in moves {
+        let side = game.side_to_move();
+        game.apply(mv).unwrap();
+        let (after, chances_after) = judge(&game, time);
+        let (after, chances_after) = (after.opponent(), 1.0 - chances_after);
+
+        annotations.push(Annotation { mv, side, before, after, chances_before, chances_after });
+        (before, chances_before) = (after.opponent(), 1.0 - chances_after);
+    }
+
+    return annotations;
+}
+
+/// What `hint` tells the player: a move to try and how the game stands.
+pub fn hint<G: Notation>(game: &G) -> String {
+    return match solve(game, HINT_TIME) {
+        Some((mv, verdict)) => advice::<G>(mv, verdict),
+        None => String::from("The game is over!"),
+    };
+}
+
+/// Suggests `mv` in the notation the game loops read.
+pub fn advice<G: Notation>(mv: G::Move, verdict: Verdict) -> String {
+    return match verdict {
+        Verdict::Unclear => format!("Try {}. It's too early to call the game.", G::format_move(mv)),
+        verdict => format!("Try {}. With best play you're {}.", G::format_move(mv), verdict),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::ultiboard::UltimateBoard;
+
+    #[test]
+    fn solves_the_normal_board() {
+        let mut board = Board::new();
+        assert_eq!(solve(&board, Duration::MAX).unwrap().1, Verdict::Drawing);
+
+        // X in the corner, O on the edge loses
+        for mv in [0, 1] {
+            board.apply(mv).unwrap();
+        }
+        assert_eq!(solve(&board, Duration::MAX).unwrap().1, Verdict::Winning);
+
+        // O has to block the diagonal, and loses anyway
+        board.apply(4).unwrap();
+        assert_eq!(solve(&board, Duration::MAX).unwrap(), (8, Verdict::Losing));
+    }
+
+    #[test]
+    fn finds_the_blunder() {
+        let mut board = Board::new();
+        // O answers the corner with an edge and loses, then X lets the win
+        // slip by making a threat O can block with one of its own
+        for mv in [0, 1, 4, 8, 2, 6] {
+            board.apply(mv).unwrap();
+        }
+
+        let annotations = analyze(&board, Duration::MAX);
+        let qualities: Vec<Quality> = annotations.iter().filter_map(Annotation::quality).collect();
+        assert_eq!(qualities, [Quality::Best, Quality::Blunder, Quality::Best, Quality::Best, Quality::Inaccuracy, Quality::Best]);
+        assert_eq!(annotations[1].side, Piece::O);
+        assert_eq!((annotations[4].before, annotations[4].after), (Verdict::Winning, Verdict::Drawing));
+    }
+
+    #[test]
+    fn flags_a_drop_in_the_chances() {
+        let annotation = |chances_before, chances_after| Annotation {
+            mv: 0,
+            side: Piece::X,
+            before: Verdict::Unclear,
+            after: Verdict::Drawing,
+            chances_before,
+            chances_after,
+        };
+
+        assert_eq!(annotation(0.8, 0.5).quality(), Some(Quality::Dubious));
+        assert_eq!(annotation(0.6, 0.5).quality(), None);
+    }
+
+    #[test]
+    fn always_has_a_hint() {
+        let board = UltimateBoard::new();
+        assert_eq!(solve(&board, Duration::ZERO).unwrap().1, Verdict::Unclear);
+        assert!(hint(&board).starts_with("Try "));
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 use crate::board::{Board, EndGame};
-use crate::input::get_input;
+use crate::notation::Notation;
+use crate::input::{command, get_input, pause};
+use crate::player::Players;
 use crate::utils::{LoopState, Piece};
 use colored::*;
 
@@ -17,27 +19,26 @@ impl fmt::Display for GameError {
     }
 }
 
-type GameResult<T> = Result<T, GameError>;
+/// Runs one turn, asking a human for a move or letting the computer make one.
+pub fn run(board: &mut Board, players: &Players) -> LoopState {
+    return turn(board, players, Board::print, "Welcome to TicTacToe!");
+}
 
-pub fn run(board: &mut Board) -> LoopState {
+/// Runs one turn on any flat board, printed with `print` under the `welcome` line.
+pub fn turn<G: Notation>(board: &mut G, players: &Players, print: fn(&G), welcome: &str) -> LoopState {
     clearscr!();
-    println!("Welcome to TicTacToe! Please input to make your move! 'q' to quit\n");
+    println!("{} Please input to make your move! 'u' to undo, 'r' to redo, 'hint' for a suggestion, 'save <file>' / 'load <file>', 'q' to quit\n", welcome);
 
-    board.print();
+    print(board);
 
-    match board.win_check() {
-        EndGame::Winner(winner) => {
-            match winner {
-                Piece::X => {
-                    println!("{}", "X wins!".purple().bold());
-                    return LoopState::Exit;
-                },
-                Piece::O => {
-                    println!("{}", "O wins!".purple().bold());
-                    return LoopState::Exit;
-                },
-                _ => { },
-            }
+    match board.outcome() {
+        EndGame::Winner(Piece::X) => {
+            println!("{}", "X wins!".purple().bold());
+            return LoopState::Exit;
+        },
+        EndGame::Winner(Piece::O) => {
+            println!("{}", "O wins!".purple().bold());
+            return LoopState::Exit;
         },
         EndGame::CatsGame => {
             println!("{}", "Cat's Game!".red().bold());
@@ -46,51 +47,33 @@ pub fn run(board: &mut Board) -> LoopState {
         _ => { },
     }
 
-    println!("({}) Make your move!", board.get_turn().to_colored_string());
+    if players.is_computer(board.side_to_move()) {
+        println!("({}) {}", board.side_to_move().to_colored_string(), "Thinking...".magenta());
+        return players.play_turn(board);
+    }
+
+    println!("({}) Make your move!", board.side_to_move().to_colored_string());
 
     let input = get_input();
 
-    if input.trim() == "q" { return LoopState::Exit; }
+    if let Some(state) = command(board, players, &input) { return state; }
 
-    match notation_to_index(input.trim()) {
+    match G::parse_move(input.trim()).ok_or(GameError::InvalidInputError) {
         Ok(index) => {
-            if let Err(error) = board.play(index) {
-                println!("{} Press 'Enter' to continue.", error); 
-
-                #[allow(unused_variables)]
-                let input = get_input();
+            let piece = board.side_to_move();
+            if let Err(error) = board.apply(index) {
+                pause(error);
 
                 return LoopState::Continue;
             }
+            players.announce::<G>(piece, index);
         },
         Err(error) => {
-            println!("{} Press 'Enter' to continue.", error);
-
-            #[allow(unused_variables)]
-            let input = get_input();
+            pause(error);
 
             return LoopState::Continue;
         },
     }
 
-    board.next_turn();
-
     return LoopState::Continue;
 }
-
-fn notation_to_index(input: &str) -> GameResult<usize> {
-    let mut it = input.chars();
-    let row = it.next().unwrap_or_else(|| 'z') as usize;
-    let col = it.next().unwrap_or_else(|| '9') as usize;
-    
-    if row < 'a' as usize || col < '1' as usize { return Err(GameError::InvalidInputError); }
-
-    let row = row - 'a' as usize;
-    let col = col - '1' as usize;
-
-    if row > crate::board::ROW_LEN || col > crate::board::ROW_LEN { return Err(GameError::InvalidInputError); }
-
-    let index = (row * 3) + col;
-    return Ok(index);
-}
-
@@ -1,11 +1,15 @@
 use std::fmt;
 
+use crate::game::Game;
 use crate::utils::Piece;
 
 // Starting at index 0
 pub const BOARD_LEN: usize = 8;
 pub const ROW_LEN: usize = 2;
-const WIN_STATES: [u16; 8] = [
+/// Every space taken.
+pub const FULL: u16 = 0b1_1111_1111;
+/// The lines through the board, with bit `i` standing for space `i`.
+pub const WIN_STATES: [u16; 8] = [
     0b1_1100_0000,
     0b0_0011_1000,
     0b0_0000_0111,
@@ -20,6 +24,7 @@ const WIN_STATES: [u16; 8] = [
 pub enum BoardError {
     SpaceTakenError,
     OutOfBoundsError,
+    GameOverError,
 }
 
 impl fmt::Display for BoardError {
@@ -27,36 +32,52 @@ impl fmt::Display for BoardError {
         match self {
             BoardError::SpaceTakenError => write!(f, "This space is already occupied!"),
             BoardError::OutOfBoundsError => write!(f, "That space does not exist!"),
+            BoardError::GameOverError => write!(f, "The game is already over!"),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EndGame {
     Winner(Piece),
     CatsGame,
     NotDone,
 }
 
+impl EndGame {
+    /// The result of a misère game, where whoever completes a line loses, if `misere`.
+    pub fn reverse_if(self, misere: bool) -> EndGame {
+        return match self {
+            EndGame::Winner(piece) if misere => EndGame::Winner(piece.opponent()),
+            other => other,
+        };
+    }
+}
+
+#[derive(Clone)]
 pub struct Board {
-    spaces: [Piece; 9],
+    xs: u16,
+    os: u16,
     turn: Piece,
+    history: Vec<usize>,
+    undone: Vec<usize>,
+    /// Completing a line loses instead of wins.
+    misere: bool,
+}
+
+impl Default for Board {
+    fn default() -> Self { Self::new() }
 }
 
 impl Board {
     pub fn new() -> Board {
         return Board {
-            spaces: [
-                Piece::Empty,
-                Piece::Empty,
-                Piece::Empty,
-                Piece::Empty,
-                Piece::Empty,
-                Piece::Empty,
-                Piece::Empty,
-                Piece::Empty,
-                Piece::Empty,
-            ],
+            xs: 0,
+            os: 0,
             turn: Piece::X,
+            history: Vec::new(),
+            undone: Vec::new(),
+            misere: false,
         };
     }
 
@@ -65,20 +86,47 @@ impl Board {
             return Err(BoardError::OutOfBoundsError);
         };
 
-        match &self.spaces[space] {
-            Piece::Empty => {
-                self.spaces[space] = self.turn;
-            }
-            _ => {
-                return Err(BoardError::SpaceTakenError);
-            }
+        let bit = 1 << space;
+        if (self.xs | self.os) & bit != 0 {
+            return Err(BoardError::SpaceTakenError);
+        }
+
+        match self.turn {
+            Piece::X => { self.xs |= bit; },
+            Piece::O => { self.os |= bit; },
+            Piece::Empty => { },
         }
 
         return Ok(());
     }
 
+    pub fn get(&self, space: usize) -> Piece {
+        return piece_at(self.xs, self.os, space);
+    }
+
+    fn make_move(&mut self, space: usize) -> Result<(), BoardError> {
+        if self.is_over() { return Err(BoardError::GameOverError); }
+
+        self.play(space)?;
+        self.history.push(space);
+        self.next_turn();
+
+        return Ok(());
+    }
+
     pub fn get_turn(&self) -> Piece { self.turn }
 
+    pub fn is_misere(&self) -> bool { self.misere }
+
+    pub fn set_misere(&mut self, misere: bool) { self.misere = misere; }
+
+    /// The empty spaces, or nothing once the game is over.
+    pub fn legal_moves(&self) -> impl Iterator<Item = usize> + '_ {
+        let empty = if matches!(self.win_check(), EndGame::NotDone) { !(self.xs | self.os) & FULL } else { 0 };
+
+        return (0..=BOARD_LEN).filter(move |&i| empty & (1 << i) != 0);
+    }
+
     pub fn next_turn(&mut self) {
         match self.turn {
             Piece::X => { self.turn = Piece::O; },
@@ -87,49 +135,107 @@ impl Board {
         }
     }
 
-    pub fn win_check(&self) -> EndGame {
-        let mut xs: u16 = 0b0_0000_0000;
-        let mut os: u16 = 0b0_0000_0000;
-
-        for i in 0..self.spaces.len() {
-            xs <<= 1;
-            os <<= 1;
-            match self.spaces[i] {
-                Piece::X => { xs ^= 0b0_0000_0001; },
-                Piece::O => { os ^= 0b0_0000_0001; },
-                Piece::Empty => { },
-            }
-        }
-
-        for state in WIN_STATES {
-            if xs & state == state { return EndGame::Winner(Piece::X); }
-            if os & state == state { return EndGame::Winner(Piece::O); }
-        }
-
-        if xs | os == 0b1_1111_1111 { return EndGame::CatsGame; }
-
-        return EndGame::NotDone;
-    }
+    pub fn win_check(&self) -> EndGame { win_check(self.xs, self.os).reverse_if(self.misere) }
 
     pub fn print(&self) {
-        let board_display = String::from(format!(
+        let board_display = format!(
             //"      1     2     3\n    _____ _____ _____\n   |     |     |     |\n a |  {}  |  {}  |  {}  |\n   |_____|_____|_____|\n   |     |     |     |\n b |  {}  |  {}  |  {}  |\n   |_____|_____|_____|\n   |     |     |     |\n c |  {}  |  {}  |  {}  |\n   |_____|_____|_____|\n",
             "      1     2     3\n                     \n         |     |     \n A    {}  |  {}  |  {}  \n    _____|_____|_____\n         |     |     \n B    {}  |  {}  |  {}  \n    _____|_____|_____\n         |     |     \n C    {}  |  {}  |  {}  \n         |     |     \n",
-            self.spaces[0].to_colored_string(),
-            self.spaces[1].to_colored_string(),
-            self.spaces[2].to_colored_string(),
-            self.spaces[3].to_colored_string(),
-            self.spaces[4].to_colored_string(),
-            self.spaces[5].to_colored_string(),
-            self.spaces[6].to_colored_string(),
-            self.spaces[7].to_colored_string(),
-            self.spaces[8].to_colored_string()
-        ));
+            self.get(0).to_colored_string(),
+            self.get(1).to_colored_string(),
+            self.get(2).to_colored_string(),
+            self.get(3).to_colored_string(),
+            self.get(4).to_colored_string(),
+            self.get(5).to_colored_string(),
+            self.get(6).to_colored_string(),
+            self.get(7).to_colored_string(),
+            self.get(8).to_colored_string()
+        );
 
         println!("{board_display}");
     }
 }
 
+impl Game for Board {
+    type Move = usize;
+    type Error = BoardError;
+
+    fn side_to_move(&self) -> Piece { self.turn }
+
+    fn legal_moves(&self) -> Vec<usize> { self.legal_moves().collect() }
+
+    fn apply(&mut self, mv: usize) -> Result<(), BoardError> {
+        self.make_move(mv)?;
+        self.undone.clear();
+
+        return Ok(());
+    }
+
+    fn undo(&mut self) -> Option<usize> {
+        let mv = self.history.pop()?;
+        self.xs &= !(1 << mv);
+        self.os &= !(1 << mv);
+        self.next_turn();
+        self.undone.push(mv);
+
+        return Some(mv);
+    }
+
+    fn redo(&mut self) -> Option<usize> {
+        let mv = self.undone.pop()?;
+        self.make_move(mv).unwrap();
+
+        return Some(mv);
+    }
+
+    fn history(&self) -> Vec<usize> { self.history.clone() }
+
+    fn outcome(&self) -> EndGame { self.win_check() }
+
+    fn winning_lines(&self) -> Vec<Vec<usize>> {
+        return winning_lines(self.xs, self.os).into_iter().map(Vec::from).collect();
+    }
+}
+
+// Functions
+
+/// Whose piece is on `space` of a 3x3 board, given each side's bits.
+pub fn piece_at(xs: u16, os: u16, space: usize) -> Piece {
+    let bit = 1 << space;
+
+    return if xs & bit != 0 {
+        Piece::X
+    } else if os & bit != 0 {
+        Piece::O
+    } else {
+        Piece::Empty
+    };
+}
+
+/// The outcome of a 3x3 board, given each side's bits.
+pub fn win_check(xs: u16, os: u16) -> EndGame {
+    for state in WIN_STATES {
+        if xs & state == state { return EndGame::Winner(Piece::X); }
+        if os & state == state { return EndGame::Winner(Piece::O); }
+    }
+
+    if xs | os == FULL { return EndGame::CatsGame; }
+
+    return EndGame::NotDone;
+}
+
+/// Every line of a 3x3 board filled by one side, as the spaces along it.
+pub fn winning_lines(xs: u16, os: u16) -> Vec<[usize; 3]> {
+    return WIN_STATES
+        .iter()
+        .filter(|&&state| xs & state == state || os & state == state)
+        .map(|&state| {
+            let mut spaces = (0..=BOARD_LEN).filter(|&space| state & (1 << space) != 0);
+            [0; 3].map(|_| spaces.next().unwrap())
+        })
+        .collect();
+}
+
 //"    1     2     3\n
 //        |     |     \n
 // a      |     |     \n
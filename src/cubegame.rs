@@ -1,18 +1,17 @@
-use crate::{utils::{LoopState, Piece}, input::get_input};
-use self::cubeboard::CubeBoard;
+use crate::{board::EndGame, utils::{LoopState, Piece}, input::{command, get_input, pause}, notation::Notation, player::Players};
+use self::{cubeboard::CubeBoard, qubicboard::QubicBoard};
 use std::fmt;
 
 use colored::*;
 
 pub mod cubeboard;
+pub mod qubicboard;
 
 #[derive(Debug)]
 enum GameError {
     InvalidInputError,
 }
 
-type GameResult<T> = Result<T, GameError>;
-
 impl fmt::Display for GameError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -21,72 +20,114 @@ impl fmt::Display for GameError {
     }
 }
 
-pub fn run(board: &mut CubeBoard) -> LoopState {
+/// Runs one turn, asking a human for a move or letting the computer make one.
+pub fn run(board: &mut CubeBoard, players: &Players) -> LoopState {
+    return turn(board, players, CubeBoard::print, "xa1 - moves to layer x, row a, and column 1");
+}
+
+/// Runs one turn of 4x4x4 Qubic.
+pub fn run_qubic(board: &mut QubicBoard, players: &Players) -> LoopState {
+    return turn(board, players, QubicBoard::print, "wa1 - moves to layer w, row a, and column 1");
+}
+
+fn turn<B>(board: &mut B, players: &Players, print: fn(&B), example: &str) -> LoopState
+where
+    B: Notation<Move = (usize, usize)>,
+{
     clearscr!();
 
-    match board.win_check() {
-        Piece::X => {
-            board.print();
+    match board.outcome() {
+        EndGame::Winner(Piece::X) => {
+            print(board);
             println!("{}", "X Wins!".purple().bold());
             return LoopState::Exit;
         },
-        Piece::O => {
-            board.print();
+        EndGame::Winner(Piece::O) => {
+            print(board);
             println!("{}", "O Wins!".purple().bold());
             return LoopState::Exit;
         },
-        Piece::Empty => { },
+        EndGame::CatsGame => {
+            print(board);
+            println!("{}", "Cat's Game!".red().bold());
+            return LoopState::Exit;
+        },
+        _ => { },
     }
 
-    board.print();
+    print(board);
 
-    println!("({}) Make your move! (Example move: xa1 - moves to layer x, row a, and column 1)", board.get_turn().to_colored_string());
+    if players.is_computer(board.side_to_move()) {
+        println!("({}) {}", board.side_to_move().to_colored_string(), "Thinking...".magenta());
+        return players.play_turn(board);
+    }
 
-    let input = get_input().to_lowercase();
-    let input = input.trim();
+    println!("({}) Make your move! (Example move: {}) 'u' to undo, 'r' to redo, 'hint' for a suggestion, 'save <file>' / 'load <file>', 'q' to quit", board.side_to_move().to_colored_string(), example);
 
-    if input == "q" { return LoopState::Exit; }
+    let input = get_input();
 
-    match notation_to_index(input) {
-        Ok((layer, index)) => {
-            if let Err(error) = board.play(layer, index) {
-                println!("{} Press 'Enter' to continue.", error);
+    if let Some(state) = command(board, players, &input) { return state; }
 
-                #[allow(unused_variables)]
-                let input = get_input();
+    let input = input.to_lowercase();
+    let input = input.trim();
+
+    match B::parse_move(input).ok_or(GameError::InvalidInputError) {
+        Ok((layer, index)) => {
+            let piece = board.side_to_move();
+            if let Err(error) = board.apply((layer, index)) {
+                pause(error);
 
                 return LoopState::Continue;
             }
+            players.announce::<B>(piece, (layer, index));
         },
         Err(error) => {
-            println!("{} Press 'Enter' to continue.", error);
-
-            #[allow(unused_variables)]
-            let input = get_input();
+            pause(error);
 
             return LoopState::Continue;
         },
     }
 
-    board.next_turn();
     return LoopState::Continue;
 }
 
-fn notation_to_index(input: &str) -> GameResult<(usize, usize)> {
-    let mut it = input.chars();
-    let layer = it.next().unwrap_or_else(|| '{') as usize;
-    let row = it.next().unwrap_or_else(|| 'z') as usize;
-    let column = it.next().unwrap_or_else(|| '9') as usize;
-
-    if layer < 'x' as usize || row < 'a' as usize || column < '1' as usize { return Err(GameError::InvalidInputError); }
-
-    let layer = layer - 'x' as usize;
-    let row = row - 'a' as usize;
-    let column = column - '1' as usize;
-
-    if layer > 2 || row > 2 || column > 2 { return Err(GameError::InvalidInputError); }
+/// Every line through a cube `size` cells wide, as masks over
+/// `layer * size * size + row * size + column`. `N` must be the number of lines.
+///
+/// Walks every cell in each of the 13 directions that go "forwards", keeping
+/// the walks that stay inside the cube for all `size` cells.
+pub const fn win_lines<const N: usize>(size: usize) -> [u64; N] {
+    let mut states = [0; N];
+    let mut count = 0;
+    let cells = (size * size * size) as isize;
+    let layer_len = (size * size) as isize;
+    let size = size as isize;
+
+    let mut direction = 0;
+    while direction < 27 {
+        let (dl, dr, dc) = (direction / 9 - 1, direction / 3 % 3 - 1, direction % 3 - 1);
+        let forwards = dl > 0 || (dl == 0 && (dr > 0 || (dr == 0 && dc > 0)));
+
+        let mut start = 0;
+        while forwards && start < cells {
+            let (l, r, c) = (start / layer_len, start / size % size, start % size);
+            let (el, er, ec) = (l + dl * (size - 1), r + dr * (size - 1), c + dc * (size - 1));
+
+            if el >= 0 && el < size && er >= 0 && er < size && ec >= 0 && ec < size {
+                let mut mask = 0;
+                let mut i = 0;
+                while i < size {
+                    mask |= 1 << ((l + dl * i) * layer_len + (r + dr * i) * size + c + dc * i);
+                    i += 1;
+                }
+                states[count] = mask;
+                count += 1;
+            }
+            start += 1;
+        }
+        direction += 1;
+    }
 
-    let index = (row * 3) + column;
-    return Ok((layer, index));
+    assert!(count == N);
+    return states;
 }
-
@@ -1,5 +1,9 @@
 use std::fmt;
+use std::marker::PhantomData;
 
+use crate::board::EndGame;
+use crate::cubegame;
+use crate::game::Game;
 use crate::utils::Piece;
 
 // Constants
@@ -7,33 +11,47 @@ use crate::utils::Piece;
 // Starting at index 0
 pub const BOARD_LEN: usize = 8;
 pub const ROW_LEN: usize = 2;
-const WIN_STATES: [u16; 8] = [
-    0b1_1100_0000,
-    0b0_0011_1000,
-    0b0_0000_0111,
-    0b1_0010_0100,
-    0b0_1001_0010,
-    0b0_0100_1001,
-    0b1_0001_0001,
-    0b0_0101_0100,
-];
+const SIZE: usize = 3;
+/// Every three-in-a-row in the cube, as a mask over `layer * 9 + index`.
+pub const WIN_STATES: [u64; 49] = cubegame::win_lines(SIZE);
 
 // Type Definitions
 
-pub struct CubeBoard {
-    layers: [Board; 3],
-    turn: Piece,
+/// The side length of a cube, and the lines that win on it.
+pub trait CubeSize: Clone {
+    const SIZE: usize;
+    /// Every line through the cube, as a mask over `layer * SIZE * SIZE + index`.
+    const WIN_STATES: &'static [u64];
 }
 
-struct Board {
-    spaces: [Piece; 9],
+/// The 3x3x3 cube.
+#[derive(Clone)]
+pub struct Three;
+
+/// 3D TicTacToe on a cube with `S::SIZE` spaces along each edge, up to 4 so
+/// every space has a bit in a `u64`.
+#[derive(Clone)]
+pub struct Cube<S: CubeSize> {
+    /// Each side's pieces, with bit `layer * LAYER_LEN + index` standing for that space.
+    xs: u64,
+    os: u64,
+    turn: Piece,
+    history: Vec<(usize, usize)>,
+    undone: Vec<(usize, usize)>,
+    /// Completing a line loses instead of wins.
+    misere: bool,
+    size: PhantomData<S>,
 }
 
+pub type CubeBoard = Cube<Three>;
+
 pub type CubeResult<T> = Result<T, CubeError>;
 
 #[derive(Debug)]
 pub enum CubeError {
     SpaceTakenError,
+    OutOfBoundsError,
+    GameOverError,
 }
 
 // Type Implementations
@@ -42,143 +60,106 @@ impl fmt::Display for CubeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             CubeError::SpaceTakenError => write!(f, "Space already taken!"),
+            CubeError::OutOfBoundsError => write!(f, "That space does not exist!"),
+            CubeError::GameOverError => write!(f, "The game is already over!"),
         }
     }
 }
 
-impl Board {
-    fn new() -> Self {
+impl CubeSize for Three {
+    const SIZE: usize = SIZE;
+    const WIN_STATES: &'static [u64] = &WIN_STATES;
+}
+
+impl<S: CubeSize> Default for Cube<S> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<S: CubeSize> Cube<S> {
+    const LAYER_LEN: usize = S::SIZE * S::SIZE;
+    const CELLS: usize = S::SIZE * Self::LAYER_LEN;
+    const FULL: u64 = u64::MAX >> (64 - Self::CELLS);
+
+    pub fn new() -> Self {
         Self {
-            spaces: [Piece::Empty; 9],
+            xs: 0,
+            os: 0,
+            turn: Piece::X,
+            history: Vec::new(),
+            undone: Vec::new(),
+            misere: false,
+            size: PhantomData,
         }
     }
 
-    fn play(&mut self, index: usize, piece: Piece) -> CubeResult<()> {
-        assert!(index < 9);
+    pub fn play(&mut self, layer: usize, index: usize) -> CubeResult<()> {
+        if layer >= S::SIZE || index >= Self::LAYER_LEN { return Err(CubeError::OutOfBoundsError); }
+
+        let bit = 1 << (layer * Self::LAYER_LEN + index);
+        if (self.xs | self.os) & bit != 0 { return Err(CubeError::SpaceTakenError); }
 
-        match self.spaces[index] {
-            Piece::Empty => { self.spaces[index] = piece; },
-            _ => { return Err(CubeError::SpaceTakenError); },
+        match self.turn {
+            Piece::X => { self.xs |= bit; },
+            Piece::O => { self.os |= bit; },
+            Piece::Empty => { },
         }
 
         return Ok(());
     }
-}
 
-impl CubeBoard {
-    pub fn new() -> Self {
-        Self {
-            layers: [Board::new(), Board::new(), Board::new()],
-            turn: Piece::X,
-        }
+    pub fn get(&self, layer: usize, index: usize) -> Piece {
+        let bit = 1 << (layer * Self::LAYER_LEN + index);
+
+        return if self.xs & bit != 0 {
+            Piece::X
+        } else if self.os & bit != 0 {
+            Piece::O
+        } else {
+            Piece::Empty
+        };
     }
 
-    pub fn play(&mut self, layer: usize, index: usize) -> CubeResult<()> {
-        assert!(layer < 3);
+    fn make_move(&mut self, mv: (usize, usize)) -> CubeResult<()> {
+        if self.is_over() { return Err(CubeError::GameOverError); }
 
-        self.layers[layer].play(index, self.turn)?;
+        self.play(mv.0, mv.1)?;
+        self.history.push(mv);
+        self.next_turn();
 
         return Ok(());
     }
 
     pub fn get_turn(&self) -> Piece { self.turn }
 
-    pub fn next_turn(&mut self) {
-        match self.turn {
-            Piece::X => { self.turn = Piece::O; },
-            Piece::O => { self.turn = Piece::X; },
-            Piece::Empty => { },
-        }
+    pub fn is_misere(&self) -> bool { self.misere }
+
+    pub fn set_misere(&mut self, misere: bool) { self.misere = misere; }
+
+    /// Every empty (layer, index) in the cube, or nothing once the game is over.
+    pub fn legal_moves(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let empty = if self.is_over() { 0 } else { !(self.xs | self.os) & Self::FULL };
+
+        return (0..Self::CELLS).filter(move |&i| empty & (1 << i) != 0).map(|i| (i / Self::LAYER_LEN, i % Self::LAYER_LEN));
     }
 
-    pub fn win_check(&self) -> Piece {
-        let mut cs_x: [u16; 9] = [0b0_0000_0000; 9];
-        let mut cs_o: [u16; 9] = [0b0_0000_0000; 9];
-        let mut index = 0;
-
-        for layer in 0..3 {
-            for space in 0..9 {
-                cs_x[index] <<= 1;
-                cs_o[index] <<= 1;
-
-                match self.layers[layer].spaces[space] {
-                    Piece::X => { cs_x[index] ^= 0b0_0000_0001; },
-                    Piece::O => { cs_o[index] ^= 0b0_0000_0001; },
-                    Piece::Empty => { },
-                }
-            }
-            index += 1;
-        }
+    pub fn next_turn(&mut self) { self.turn = self.turn.opponent(); }
 
-        for row in 0..3 {
-            for layer in 0..3 {
-                for col in 0..3 {
-                    cs_x[index] <<= 1;
-                    cs_o[index] <<= 1;
-
-                    match self.layers[layer].spaces[(row * 3) + col] {
-                        Piece::X => { cs_x[index] ^= 0b0_0000_0001; },
-                        Piece::O => { cs_o[index] ^= 0b0_0000_0001; },
-                        Piece::Empty => { },
-                    }
-                }
-            }
-            index += 1;
-        }
+    pub fn win_check(&self) -> EndGame { self.line_check().reverse_if(self.misere) }
 
-        for col in 0..3 {
-            for layer in 0..3 {
-                for row in 0..3 {
-                    cs_x[index] <<= 1;
-                    cs_o[index] <<= 1;
-
-                    match self.layers[layer].spaces[(row * 3) + col] {
-                        Piece::X => { cs_x[index] ^= 0b0_0000_0001; },
-                        Piece::O => { cs_o[index] ^= 0b0_0000_0001; },
-                        Piece::Empty => { },
-                    }
-                }
-            }
-            index += 1;
+    /// Who has a line, whatever that means for the result.
+    fn line_check(&self) -> EndGame {
+        for &state in S::WIN_STATES {
+            if self.xs & state == state { return EndGame::Winner(Piece::X); }
+            if self.os & state == state { return EndGame::Winner(Piece::O); }
         }
 
-        for state in WIN_STATES {
-            for cs in cs_x {
-                if cs & state == state { return Piece::X; }
-            }
-            for cs in cs_o {
-                if cs & state == state { return Piece::O; }
-            }
-        }
+        if self.xs | self.os == Self::FULL { return EndGame::CatsGame; }
 
-        if cs_x[0] & 0b1_0000_0000 == 0b1_0000_0000 &&
-           cs_x[1] & 0b0_0001_0000 == 0b0_0001_0000 &&
-           cs_x[2] & 0b0_0000_0001 == 0b0_0000_0001 { return Piece::X; }
-        if cs_x[0] & 0b0_0000_0001 == 0b0_0000_0001 &&
-           cs_x[1] & 0b0_0001_0000 == 0b0_0001_0000 &&
-           cs_x[2] & 0b1_0000_0000 == 0b1_0000_0000 { return Piece::X; }
-        if cs_x[0] & 0b0_0100_0000 == 0b0_0100_0000 &&
-           cs_x[1] & 0b0_0001_0000 == 0b0_0001_0000 &&
-           cs_x[2] & 0b0_0000_0100 == 0b0_0000_0100 { return Piece::X; }
-        if cs_x[0] & 0b0_0000_0100 == 0b0_0000_0100 &&
-           cs_x[1] & 0b0_0001_0000 == 0b0_0001_0000 &&
-           cs_x[2] & 0b0_0100_0000 == 0b0_0100_0000 { return Piece::X; }
-        if cs_o[0] & 0b1_0000_0000 == 0b1_0000_0000 &&
-           cs_o[1] & 0b0_0001_0000 == 0b0_0001_0000 &&
-           cs_o[2] & 0b0_0000_0001 == 0b0_0000_0001 { return Piece::O; }
-        if cs_o[0] & 0b0_0000_0001 == 0b0_0000_0001 &&
-           cs_o[1] & 0b0_0001_0000 == 0b0_0001_0000 &&
-           cs_o[2] & 0b1_0000_0000 == 0b1_0000_0000 { return Piece::O; }
-        if cs_o[0] & 0b0_0100_0000 == 0b0_0100_0000 &&
-           cs_o[1] & 0b0_0001_0000 == 0b0_0001_0000 &&
-           cs_o[2] & 0b0_0000_0100 == 0b0_0000_0100 { return Piece::O; }
-        if cs_o[0] & 0b0_0000_0100 == 0b0_0000_0100 &&
-           cs_o[1] & 0b0_0001_0000 == 0b0_0001_0000 &&
-           cs_o[2] & 0b0_0100_0000 == 0b0_0100_0000 { return Piece::O; }
-
-        return Piece::Empty;
+        return EndGame::NotDone;
     }
+}
 
+impl CubeBoard {
     pub fn print(&self) {
         println!(
             "
@@ -213,37 +194,146 @@ impl CubeBoard {
                             C|  {}   \\   {}   \\   {}   \\|
                              |_______\\_______\\_______|
          ",
-         self.layers[0].spaces[0].to_colored_string(),
-         self.layers[0].spaces[1].to_colored_string(),
-         self.layers[0].spaces[2].to_colored_string(),
-         self.layers[0].spaces[3].to_colored_string(),
-         self.layers[0].spaces[4].to_colored_string(),
-         self.layers[0].spaces[5].to_colored_string(),
-         self.layers[0].spaces[6].to_colored_string(),
-         self.layers[0].spaces[7].to_colored_string(),
-         self.layers[0].spaces[8].to_colored_string(),
-         self.layers[1].spaces[0].to_colored_string(),
-         self.layers[1].spaces[1].to_colored_string(),
-         self.layers[1].spaces[2].to_colored_string(),
-         self.layers[1].spaces[3].to_colored_string(),
-         self.layers[1].spaces[4].to_colored_string(),
-         self.layers[1].spaces[5].to_colored_string(),
-         self.layers[1].spaces[6].to_colored_string(),
-         self.layers[1].spaces[7].to_colored_string(),
-         self.layers[1].spaces[8].to_colored_string(),
-         self.layers[2].spaces[0].to_colored_string(),
-         self.layers[2].spaces[1].to_colored_string(),
-         self.layers[2].spaces[2].to_colored_string(),
-         self.layers[2].spaces[3].to_colored_string(),
-         self.layers[2].spaces[4].to_colored_string(),
-         self.layers[2].spaces[5].to_colored_string(),
-         self.layers[2].spaces[6].to_colored_string(),
-         self.layers[2].spaces[7].to_colored_string(),
-         self.layers[2].spaces[8].to_colored_string(),
+         self.get(0, 0).to_colored_string(),
+         self.get(0, 1).to_colored_string(),
+         self.get(0, 2).to_colored_string(),
+         self.get(0, 3).to_colored_string(),
+         self.get(0, 4).to_colored_string(),
+         self.get(0, 5).to_colored_string(),
+         self.get(0, 6).to_colored_string(),
+         self.get(0, 7).to_colored_string(),
+         self.get(0, 8).to_colored_string(),
+         self.get(1, 0).to_colored_string(),
+         self.get(1, 1).to_colored_string(),
+         self.get(1, 2).to_colored_string(),
+         self.get(1, 3).to_colored_string(),
+         self.get(1, 4).to_colored_string(),
+         self.get(1, 5).to_colored_string(),
+         self.get(1, 6).to_colored_string(),
+         self.get(1, 7).to_colored_string(),
+         self.get(1, 8).to_colored_string(),
+         self.get(2, 0).to_colored_string(),
+         self.get(2, 1).to_colored_string(),
+         self.get(2, 2).to_colored_string(),
+         self.get(2, 3).to_colored_string(),
+         self.get(2, 4).to_colored_string(),
+         self.get(2, 5).to_colored_string(),
+         self.get(2, 6).to_colored_string(),
+         self.get(2, 7).to_colored_string(),
+         self.get(2, 8).to_colored_string(),
         );
     }
 }
 
+impl<S: CubeSize> Game for Cube<S> {
+    /// (layer, index)
+    type Move = (usize, usize);
+    type Error = CubeError;
+
+    fn side_to_move(&self) -> Piece { self.turn }
+
+    fn legal_moves(&self) -> Vec<(usize, usize)> { self.legal_moves().collect() }
+
+    fn apply(&mut self, mv: (usize, usize)) -> CubeResult<()> {
+        self.make_move(mv)?;
+        self.undone.clear();
+
+        return Ok(());
+    }
+
+    fn undo(&mut self) -> Option<(usize, usize)> {
+        let (layer, index) = self.history.pop()?;
+        let bit = !(1 << (layer * Self::LAYER_LEN + index));
+        self.xs &= bit;
+        self.os &= bit;
+        self.next_turn();
+        self.undone.push((layer, index));
+
+        return Some((layer, index));
+    }
+
+    fn redo(&mut self) -> Option<(usize, usize)> {
+        let mv = self.undone.pop()?;
+        self.make_move(mv).unwrap();
+
+        return Some(mv);
+    }
+
+    fn history(&self) -> Vec<(usize, usize)> { self.history.clone() }
+
+    fn outcome(&self) -> EndGame { self.win_check() }
+
+    fn winning_lines(&self) -> Vec<Vec<(usize, usize)>> {
+        return S::WIN_STATES
+            .iter()
+            .filter(|&&state| self.xs & state == state || self.os & state == state)
+            .map(|&state| (0..Self::CELLS).filter(|&i| state & (1 << i) != 0).map(|i| (i / Self::LAYER_LEN, i % Self::LAYER_LEN)).collect())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    fn fill(pieces: &[Piece]) -> CubeBoard {
+        let mut board = CubeBoard::new();
+        for (i, &piece) in pieces.iter().enumerate() {
+            match piece {
+                Piece::X => { board.xs |= 1 << i; },
+                Piece::O => { board.os |= 1 << i; },
+                Piece::Empty => { },
+            }
+        }
+        return board;
+    }
+
+    #[test]
+    fn win_states_are_distinct_lines() {
+        for (i, state) in WIN_STATES.iter().enumerate() {
+            assert_eq!(state.count_ones(), 3);
+            assert!(!WIN_STATES[i + 1..].contains(state));
+        }
+    }
+
+    /// A 3x3x3 cube can't be filled without making a line, so the closest thing
+    /// to a drawn cube is checking that a full one never reports a cat's game.
+    #[test]
+    fn every_full_cube_has_a_winner() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut pieces = [[Piece::X; 14].as_slice(), [Piece::O; 13].as_slice()].concat();
+
+        for _ in 0..1000 {
+            pieces.shuffle(&mut rng);
+            assert!(matches!(fill(&pieces).win_check(), EndGame::Winner(_)));
+        }
+    }
+
+    #[test]
+    fn played_out_games_end() {
+        let mut rng = StdRng::seed_from_u64(8);
+
+        for _ in 0..100 {
+            let mut board = CubeBoard::new();
+            while let Some(&mv) = Game::legal_moves(&board).choose(&mut rng) {
+                board.apply(mv).unwrap();
+            }
+            assert_ne!(board.win_check(), EndGame::NotDone);
+            assert_eq!(board.legal_moves().count(), 0);
+        }
+    }
+
+    #[test]
+    fn rejects_spaces_off_the_cube() {
+        let mut board = CubeBoard::new();
+
+        assert!(matches!(board.apply((3, 0)), Err(CubeError::OutOfBoundsError)));
+        assert!(matches!(board.apply((0, 9)), Err(CubeError::OutOfBoundsError)));
+        assert!(board.history().is_empty());
+    }
+}
+
 //
 //    ________________________
 //    \       \       \       \
@@ -0,0 +1,74 @@
+use crate::cubegame;
+use crate::cubegame::cubeboard::{Cube, CubeSize};
+
+// Constants
+
+pub const SIZE: usize = 4;
+pub const LAYER_LEN: usize = SIZE * SIZE;
+/// Every four-in-a-row in the cube, as a mask over `layer * 16 + row * 4 + column`.
+pub const WIN_STATES: [u64; 76] = cubegame::win_lines(SIZE);
+
+// Type Definitions
+
+/// The 4x4x4 cube.
+#[derive(Clone)]
+pub struct Four;
+
+/// 4x4x4 3D TicTacToe, also known as Qubic.
+pub type QubicBoard = Cube<Four>;
+
+// Type Implementations
+
+impl CubeSize for Four {
+    const SIZE: usize = SIZE;
+    const WIN_STATES: &'static [u64] = &WIN_STATES;
+}
+
+impl QubicBoard {
+    pub fn print(&self) {
+        let mut display = String::new();
+
+        for layer in 0..SIZE {
+            display.push_str(&format!("\n  {}      1   2   3   4\n", (b'W' + layer as u8) as char));
+
+            for row in 0..SIZE {
+                let indent = " ".repeat(row * 2);
+                let cells = (0..SIZE)
+                    .map(|col| format!(" {} ", self.get(layer, row * SIZE + col).to_colored_string()))
+                    .collect::<Vec<String>>()
+                    .join("\\");
+
+                display.push_str(&format!("     {}{} \\{}\\\n", indent, (b'A' + row as u8) as char, cells));
+            }
+        }
+
+        println!("{display}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::EndGame;
+    use crate::game::Game;
+    use crate::utils::Piece;
+
+    #[test]
+    fn win_states_are_distinct_lines() {
+        for (i, state) in WIN_STATES.iter().enumerate() {
+            assert_eq!(state.count_ones(), 4);
+            assert!(!WIN_STATES[i + 1..].contains(state));
+        }
+    }
+
+    #[test]
+    fn space_diagonal_wins() {
+        let mut board = QubicBoard::new();
+        for mv in [(0, 0), (0, 1), (1, 5), (0, 2), (2, 10), (0, 4), (3, 15)] {
+            board.apply(mv).unwrap();
+        }
+
+        assert_eq!(board.win_check(), EndGame::Winner(Piece::X));
+        assert_eq!(board.legal_moves().count(), 0);
+    }
+}
@@ -0,0 +1,216 @@
+//! A GTP-style text protocol for letting other programs play. Each command is
+//! a line, answered by one line starting with `=` on success or `?` with an
+//! error otherwise:
+//!
+//! ```text
+//! newgame ultimate    =
+//! play b2a1           =
+//! genmove             = a1c3
+//! legal               = c1a1 c1a2 ...
+//! result              = *
+//! quit                =
+//! ```
+//!
+//! `newgame` takes any variant written in game records, moves use the same
+//! notation as records, and `result` answers like a record's `Result` header.
+//! `genmove` plays the engine's move for the side to move before answering.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use crate::board::{Board, EndGame};
+use crate::cubegame::{cubeboard::CubeBoard, qubicboard::QubicBoard};
+use crate::game::Game;
+use crate::mnkboard::MnkBoard;
+use crate::notation::Notation;
+use crate::player::Player;
+use crate::record;
+use crate::ultiboard::UltimateBoard;
+
+// Type Definitions
+
+/// A child process speaking the protocol, from the side asking for moves.
+#[derive(Debug)]
+pub struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+/// The game behind a `newgame`, whatever its variant.
+trait Session {
+    fn play(&mut self, mv: &str) -> Result<(), String>;
+
+    fn genmove(&mut self, engine: &Player) -> Result<String, String>;
+
+    fn legal(&self) -> Vec<String>;
+
+    fn result(&self) -> EndGame;
+}
+
+// Type Implementations
+
+impl<G: Notation> Session for G {
+    fn play(&mut self, mv: &str) -> Result<(), String> {
+        let mv = G::parse_move(mv).ok_or_else(|| format!("bad move {}", mv))?;
+        return self.apply(mv).map_err(|error| error.to_string());
+    }
+
+    fn genmove(&mut self, engine: &Player) -> Result<String, String> {
+        let mv = engine.choose_move(self).ok_or_else(|| String::from("no move"))?;
+        self.apply(mv).map_err(|error| error.to_string())?;
+
+        return Ok(G::format_move(mv));
+    }
+
+    fn legal(&self) -> Vec<String> {
+        return Game::legal_moves(self).into_iter().map(G::format_move).collect();
+    }
+
+    fn result(&self) -> EndGame { self.outcome() }
+}
+
+impl Client {
+    /// Starts `command`, split on whitespace into the program and its arguments.
+    pub fn spawn(command: &str) -> io::Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No engine command given!"))?;
+
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        return Ok(Self {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+        });
+    }
+
+    /// Sends one command and returns the text after the `=` of its answer.
+    pub fn send(&mut self, command: &str) -> io::Result<String> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()?;
+
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The engine quit!"));
+        }
+
+        let line = line.trim();
+        return match line.strip_prefix('=') {
+            Some(answer) => Ok(String::from(answer.trim())),
+            None => Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", command, line))),
+        };
+    }
+
+    /// Sets the engine up with `game`, then asks it for the side to move's move.
+    /// The whole game is sent each time, so undos and loads never leave the
+    /// engine behind.
+    pub fn genmove<G: Notation>(&mut self, game: &G) -> io::Result<G::Move> {
+        self.send(&format!("newgame {}", game.variant()))?;
+        for mv in game.history() {
+            self.send(&format!("play {}", G::format_move(mv)))?;
+        }
+
+        let answer = self.send("genmove")?;
+        return G::parse_move(&answer)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Bad move from engine: {}", answer)));
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.wait();
+    }
+}
+
+// Functions
+
+/// Answers commands from `input` until it ends or says `quit`. Moves are
+/// chosen by `engine`, or by `Player::default_for` the variant if it's `None`.
+pub fn serve<R: BufRead, W: Write>(input: R, mut output: W, engine: Option<Player>) -> io::Result<()> {
+    let mut session: Option<(Box<dyn Session>, Player)> = None;
+
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+
+        let answer = match (words.next(), words.next(), session.as_mut()) {
+            (None, _, _) => { continue; },
+            (Some("quit"), None, _) => {
+                writeln!(output, "=")?;
+                output.flush()?;
+                return Ok(());
+            },
+            (Some("newgame"), Some(variant), _) => match new_session(variant) {
+                Some(game) => {
+                    session = Some((game, engine.clone().unwrap_or_else(|| Player::default_for(variant))));
+                    Ok(String::new())
+                },
+                None => Err(format!("unknown variant {}", variant)),
+            },
+            (Some("play"), Some(mv), Some((game, _))) => game.play(mv).map(|_| String::new()),
+            (Some("genmove"), None, Some((game, engine))) => game.genmove(engine),
+            (Some("legal"), None, Some((game, _))) => Ok(game.legal().join(" ")),
+            (Some("result"), None, Some((game, _))) => Ok(String::from(record::result_to_str(game.result()))),
+            (Some("play" | "genmove" | "legal" | "result"), _, None) => Err(String::from("no game, use newgame first")),
+            _ => Err(format!("unknown command {}", line.trim())),
+        };
+
+        match answer {
+            Ok(answer) if answer.is_empty() => writeln!(output, "=")?,
+            Ok(answer) => writeln!(output, "= {}", answer)?,
+            Err(error) => writeln!(output, "? {}", error)?,
+        }
+        output.flush()?;
+    }
+
+    return Ok(());
+}
+
+fn new_session(variant: &str) -> Option<Box<dyn Session>> {
+    if let Some(game) = Board::from_variant(variant) { return Some(Box::new(game)); }
+    if let Some(game) = UltimateBoard::from_variant(variant) { return Some(Box::new(game)); }
+    if let Some(game) = CubeBoard::from_variant(variant) { return Some(Box::new(game)); }
+    if let Some(game) = QubicBoard::from_variant(variant) { return Some(Box::new(game)); }
+    if let Some(game) = MnkBoard::from_variant(variant) { return Some(Box::new(game)); }
+
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(commands: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        serve(commands.as_bytes(), &mut output, Some(Player::Negamax(9))).unwrap();
+
+        return String::from_utf8(output).unwrap().lines().map(String::from).collect();
+    }
+
+    #[test]
+    fn plays_a_session() {
+        let answers = answers("legal\nnewgame normal\nplay a1\nplay b1\nplay a2\nplay b2\ngenmove\nresult\nplay a1\nquit\nlegal\n");
+
+        assert!(answers[0].starts_with('?'));
+        assert_eq!(answers[1..6], ["=", "=", "=", "=", "="]);
+        assert_eq!(answers[6], "= a3");
+        assert_eq!(answers[7], "= 1-0");
+        assert!(answers[8].starts_with('?'));
+        assert_eq!(answers[9], "=");
+        assert_eq!(answers.len(), 10);
+    }
+
+    #[test]
+    fn lists_legal_moves() {
+        let answers = answers("newgame ultimate\nplay b2a1\nlegal\nnewgame mnk:4:4:4\nlegal\n");
+
+        assert_eq!(answers[2], "= a1a1 a1a2 a1a3 a1b1 a1b2 a1b3 a1c1 a1c2 a1c3");
+        assert_eq!(answers[4].split(' ').count(), 17);
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+
+use crate::board::EndGame;
+use crate::player::Players;
+use crate::utils::Piece;
+
+/// Common interface implemented by every board variant, so that tools, bots
+/// and tests can be written once and run against all of them.
+pub trait Game: Clone {
+    /// A single move in this variant.
+    type Move: Copy + PartialEq + fmt::Debug;
+    /// The error returned when a move can not be applied.
+    type Error: fmt::Display + fmt::Debug;
+
+    /// The piece whose turn it is.
+    fn side_to_move(&self) -> Piece;
+
+    /// Every move the side to move may legally make. Empty once the game is over.
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    /// Plays `mv` for the side to move and passes the turn.
+    fn apply(&mut self, mv: Self::Move) -> Result<(), Self::Error>;
+
+    /// Takes back the last move made with `apply`, returning it.
+    fn undo(&mut self) -> Option<Self::Move>;
+
+    /// Plays the last move taken back with `undo` again, returning it. Any new
+    /// `apply` forgets the moves that could have been redone.
+    fn redo(&mut self) -> Option<Self::Move>;
+
+    /// Every move made so far, oldest first.
+    fn history(&self) -> Vec<Self::Move>;
+
+    /// Whether the game has been won, drawn, or is still going.
+    fn outcome(&self) -> EndGame;
+
+    /// The completed lines that decided the game, each as the cells along it
+    /// written as moves. Empty until someone completes a line.
+    fn winning_lines(&self) -> Vec<Vec<Self::Move>>;
+
+    fn is_over(&self) -> bool {
+        !matches!(self.outcome(), EndGame::NotDone)
+    }
+}
+
+/// Takes back the last move, and the computer's move before it if that leaves
+/// the computer to play, so a human gets their own turn back. Does nothing
+/// in network games.
+pub fn undo_turn<G: Game>(game: &mut G, players: &Players) {
+    if players.is_networked() { return; }
+
+    game.undo();
+    if players.is_computer(game.side_to_move()) { game.undo(); }
+}
+
+/// Redoes the last undone move, and the computer's reply after it if there is one.
+pub fn redo_turn<G: Game>(game: &mut G, players: &Players) {
+    if players.is_networked() { return; }
+
+    game.redo();
+    if players.is_computer(game.side_to_move()) { game.redo(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, ultiboard::UltimateBoard, cubegame::cubeboard::CubeBoard};
+
+    fn play_out_and_back<G: Game>(mut game: G) {
+        let start = game.legal_moves();
+        let mut played = 0;
+
+        while let Some(&mv) = game.legal_moves().last() {
+            game.apply(mv).unwrap();
+            played += 1;
+        }
+        assert!(game.is_over());
+
+        for _ in 0..played {
+            assert!(game.undo().is_some());
+        }
+        assert!(game.undo().is_none());
+        assert_eq!(game.legal_moves(), start);
+        assert_eq!(game.side_to_move(), Piece::X);
+
+        for _ in 0..played {
+            assert!(game.redo().is_some());
+        }
+        assert!(game.redo().is_none());
+        assert!(game.is_over());
+        assert_eq!(game.history().len(), played);
+    }
+
+    #[test]
+    fn every_variant_plays_out_and_undoes() {
+        play_out_and_back(Board::new());
+        play_out_and_back(UltimateBoard::new());
+        play_out_and_back(CubeBoard::new());
+    }
+}
@@ -4,9 +4,54 @@ macro_rules! clearscr {
     };
 }
 
+use std::fmt;
+
+use crate::ai::solver;
+use crate::game;
+use crate::notation::Notation;
+use crate::player::Players;
+use crate::record;
+use crate::utils::LoopState;
+
 pub fn get_input() -> String {
     let mut input = String::new();
     std::io::stdin().read_line(&mut input).expect("Input failed!");
     return input;
 }
 
+
+/// Shows `message` and waits for the player to press 'Enter'.
+pub fn pause(message: impl fmt::Display) {
+    println!("{} Press 'Enter' to continue.", message);
+    get_input();
+}
+
+/// Handles the inputs every typed game loop shares: `q`, `u`, `r`, `hint`,
+/// `save <file>` and `load <file>`. `None` if `input` was none of them, so it
+/// should be read as a move.
+pub fn command<G: Notation>(game: &mut G, players: &Players, input: &str) -> Option<LoopState> {
+    if let Some(result) = record::run_command(game, players, input) {
+        match result {
+            Ok(message) => pause(message),
+            Err(error) => pause(error),
+        }
+        return Some(LoopState::Continue);
+    }
+
+    match input.trim().to_lowercase().as_str() {
+        "q" => Some(LoopState::Exit),
+        "u" => {
+            game::undo_turn(game, players);
+            Some(LoopState::Continue)
+        },
+        "r" => {
+            game::redo_turn(game, players);
+            Some(LoopState::Continue)
+        },
+        "hint" => {
+            pause(solver::hint(game));
+            Some(LoopState::Continue)
+        },
+        _ => None,
+    }
+}
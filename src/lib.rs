@@ -1,9 +1,23 @@
+#![allow(clippy::needless_return)]
+
 #[macro_use]
 pub mod input;
 pub mod board;
+pub mod mnkboard;
+pub mod mnkgame;
+pub mod game;
 pub mod basic_game;
 pub mod ultiboard;
 pub mod ultigame;
 pub mod cubegame;
 pub mod utils;
+pub mod ai;
+pub mod notation;
+pub mod player;
+pub mod record;
+pub mod network;
+pub mod engine;
+pub mod tournament;
+pub mod tui;
+pub mod symmetry;
 
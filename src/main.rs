@@ -1,66 +1,452 @@
+#![allow(clippy::needless_return)]
+
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use colored::*;
 use tictactoe::{
+    ai::solver::{self, Quality, Verdict},
     basic_game,
     board::Board,
+    mnkgame, mnkboard::MnkBoard,
     ultigame,
-    ultiboard::UltimateBoard, input::get_input, utils::LoopState, cubegame::{cubeboard::CubeBoard, self},
+    ultiboard::{Rules, UltimateBoard}, input::get_input, utils::{LoopState, Piece}, cubegame::{cubeboard::CubeBoard, qubicboard::QubicBoard, self},
+    notation::{self, Notation}, player::{Player, Players}, record::{GameRecord, RecordResult},
+    network::{self, Connection}, engine, tournament::Tournament, tui::{self, Layout},
 };
 
-enum Game {
-    TicTacToe,
+#[derive(Parser)]
+#[command(name = "tictactoe", about = "Normal, Ultimate and 3D TicTacToe in the terminal.")]
+struct Cli {
+    /// Without a command, an interactive menu is shown
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Type coordinates at a prompt instead of moving a cursor around the board
+    #[arg(long, global = true)]
+    classic: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play a game
+    Play {
+        #[arg(long, value_enum, default_value_t = Variant::Normal)]
+        variant: Variant,
+        #[command(flatten)]
+        options: Options,
+        /// Who plays X: human, ai:negamax[:depth], ai:mcts[:iterations|:<seconds>s] or engine:<command>
+        #[arg(long, default_value = "human")]
+        x: Player,
+        /// Who plays O, in the same format as --x
+        #[arg(long, default_value = "human")]
+        o: Player,
+    },
+    /// Step through a saved game one move at a time
+    Replay {
+        file: String,
+    },
+    /// Judge every move of a saved game, then ask the computer for the best move at the end
+    Analyze {
+        file: String,
+        /// The engine to ask, in the same format as play's --x
+        #[arg(long)]
+        engine: Option<Player>,
+        /// Seconds to search each position for, and as long again for MCTS when the
+        /// search can't settle it; the normal board is always solved
+        #[arg(long, default_value_t = 1.0)]
+        time: f64,
+    },
+    /// Play computer players against each other, round-robin
+    Tournament {
+        /// Any variant but mnk
+        #[arg(long, value_enum, default_value_t = Variant::Normal)]
+        variant: Variant,
+        /// Games per pairing, alternating who plays X
+        #[arg(long, default_value_t = 10)]
+        games: usize,
+        /// The players taking part, in the same format as play's --x
+        #[arg(default_values = ["ai:negamax:4", "ai:mcts:1000", "ai:greedy", "ai:random"])]
+        entrants: Vec<Player>,
+    },
+    /// Speak the engine protocol over stdin and stdout, so other programs can ask for moves
+    Engine {
+        /// The engine choosing moves, in the same format as play's --x.
+        /// Defaults to the strongest built-in one for each variant
+        #[arg(long)]
+        engine: Option<Player>,
+    },
+    /// Wait for another player to join a game over the network
+    Host {
+        #[arg(long, default_value_t = network::DEFAULT_PORT)]
+        port: u16,
+        #[arg(long, value_enum, default_value_t = Variant::Ultimate)]
+        variant: Variant,
+        #[command(flatten)]
+        options: Options,
+        /// The side played by the host
+        #[arg(long, value_enum, default_value_t = Side::X)]
+        side: Side,
+        /// Who plays for this side, in the same format as play's --x
+        #[arg(long, default_value = "human")]
+        player: Player,
+    },
+    /// Join a game hosted over the network, playing whichever side the host didn't take
+    Join {
+        #[arg(default_value = "127.0.0.1:7878")]
+        address: String,
+        /// Who plays for this side, in the same format as play's --x
+        #[arg(long, default_value = "human")]
+        player: Player,
+    },
+}
+
+/// How the chosen variant is set up.
+#[derive(Args, Default)]
+struct Options {
+    /// Width, height and how many in a row win, for the mnk variant
+    #[arg(long, value_name = "WIDTH,HEIGHT,K", value_parser = parse_mnk, default_value = "4,4,4")]
+    mnk: MnkBoard,
+    /// House rules for the ultimate variant: standard, or any of 'both' (drawn
+    /// boards count for both players), 'any' (a free choice may pick a won
+    /// board) and 'majority' (a drawn game goes to whoever won more boards)
+    #[arg(long, default_value = "standard")]
+    rules: Rules,
+    /// Play misère, where completing a line loses
+    #[arg(long)]
+    misere: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Variant {
+    Normal,
     Ultimate,
     Cube,
-    NoGame,
+    /// 4x4x4 3D TicTacToe
+    Qubic,
+    /// Any size of board and length of line, e.g. 15,15,5 for Gomoku
+    Mnk,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Side {
+    X,
+    O,
 }
 
 fn main() {
-    loop {
-        let mut game = Game::NoGame;
+    let cli = Cli::parse();
 
-        println!("Would you like to play 'n'ormal TicTacToe, 'u'ltimate TicTacToe, or '3'D TicTacToe?");
+    let classic = cli.classic;
+
+    let result = match cli.command {
+        None => menu(classic),
+        Some(Command::Play { variant, options, x, o }) => play(&variant_name(variant, &options), &Players::new(x, o), classic),
+        Some(Command::Replay { file }) => replay(&file).map_err(|error| error.to_string()),
+        Some(Command::Analyze { file, engine, time }) => match Duration::try_from_secs_f64(time) {
+            Ok(time) => analyze(&file, engine, time).map_err(|error| error.to_string()),
+            Err(error) => Err(error.to_string()),
+        },
+        Some(Command::Tournament { variant, games, entrants }) => tournament(variant, Tournament::new(entrants, games)),
+        Some(Command::Engine { engine: player }) => {
+            engine::serve(std::io::stdin().lock(), std::io::stdout().lock(), player).map_err(|error| error.to_string())
+        },
+        Some(Command::Host { port, variant, options, side, player }) => host(port, &variant_name(variant, &options), side, player, classic),
+        Some(Command::Join { address, player }) => join(&address, player, classic),
+    };
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+fn menu(classic: bool) -> Result<(), String> {
+    loop {
+        println!("Would you like to play 'n'ormal TicTacToe, 'u'ltimate TicTacToe, '3'D TicTacToe, '4'x4x4 Qubic, or 'm',n,k TicTacToe?");
         let input = get_input().to_lowercase();
         let input = input.trim();
 
-        match input {
-            "n" => { game = Game::TicTacToe; },
-            "u" => { game = Game::Ultimate; },
-            "3" => { game = Game::Cube; },
-            _ => { },
+        let variant = match input {
+            "n" => Variant::Normal,
+            "u" => Variant::Ultimate,
+            "3" => Variant::Cube,
+            "4" => Variant::Qubic,
+            "m" => Variant::Mnk,
+            _ => { return Ok(()); },
+        };
+
+        let mut options = Options::default();
+        if let Variant::Mnk = variant {
+            println!("Enter the width, height and how many in a row win (e.g. '15,15,5' for Gomoku).");
+            match parse_mnk(&get_input()) {
+                Ok(board) => { options.mnk = board; },
+                Err(error) => {
+                    println!("{}", error);
+                    continue;
+                },
+            }
         }
 
-        match game {
-            Game::TicTacToe => { tictactoe(); },
-            Game::Ultimate => { ultimate(); },
-            Game::Cube => { cube(); },
-            Game::NoGame => { break; }
+        if let Variant::Ultimate = variant {
+            println!("Press 'Enter' for the standard rules, or pick any of 'both' (drawn boards count for both players), 'any' (a free choice may pick a won board) and 'majority' (a drawn game goes to whoever won more boards), separated by commas.");
+            match get_input().parse::<Rules>() {
+                Ok(rules) => { options.rules = rules; },
+                Err(error) => {
+                    println!("{}", error);
+                    continue;
+                },
+            }
         }
+
+        println!("Play misère, where completing a line loses? 'y' or 'n' (press 'Enter')");
+        options.misere = get_input().trim().eq_ignore_ascii_case("y");
+
+        let players = choose_players(variant);
+        play(&variant_name(variant, &options), &players, classic)?;
     }
 }
 
-fn ultimate() {
-    let mut board = UltimateBoard::new();
-    let mut result = LoopState::Continue;
+/// Plays a new game of the variant written as `name`.
+fn play(name: &str, players: &Players, classic: bool) -> Result<(), String> {
+    return match variant_named(name) {
+        Variant::Normal => run_game::<Board>(name, players, basic_game::run, classic),
+        Variant::Ultimate => run_game::<UltimateBoard>(name, players, ultigame::run, classic),
+        Variant::Cube => run_game::<CubeBoard>(name, players, cubegame::run, classic),
+        Variant::Qubic => run_game::<QubicBoard>(name, players, cubegame::run_qubic, classic),
+        Variant::Mnk => run_game::<MnkBoard>(name, players, mnkgame::run, classic),
+    };
+}
 
-    while let LoopState::Continue = result {
-        result = ultigame::run(&mut board);
+/// Plays full-screen, or through the typed-coordinate loop `run` if `classic`.
+fn run_game<G: Layout>(name: &str, players: &Players, run: fn(&mut G, &Players) -> LoopState, classic: bool) -> Result<(), String> {
+    let mut board = G::from_variant(name).ok_or_else(|| format!("Unknown variant: {}", name))?;
+
+    let result = if classic {
+        run_loop(&mut board, players, run);
+        Ok(())
+    } else {
+        tui::play(&mut board, players).map_err(|error| error.to_string())
+    };
+
+    players.hang_up();
+
+    if result.is_ok() && board.is_over() {
+        println!("Press 'a' and 'Enter' to see how every move went, or just 'Enter' to leave.");
+        if get_input().trim().eq_ignore_ascii_case("a") { print_analysis(&board, solver::HINT_TIME); }
     }
+
+    return result;
 }
 
-fn tictactoe() {
-    let mut board = Board::new();
+fn run_loop<G>(board: &mut G, players: &Players, run: fn(&mut G, &Players) -> LoopState) {
     let mut result = LoopState::Continue;
 
     while let LoopState::Continue = result {
-        result = basic_game::run(&mut board);
+        result = run(board, players);
     }
 }
 
-fn cube() {
-    let mut board = CubeBoard::new();
-    let mut result = LoopState::Continue;
+fn default_engine(variant: Variant) -> Player {
+    return Player::default_for(&variant_name(variant, &Options::default()));
+}
 
-    while let LoopState::Continue = result {
-        result = cubegame::run(&mut board);
+fn parse_mnk(input: &str) -> Result<MnkBoard, String> {
+    let sizes = input.trim()
+        .split(',')
+        .map(|size| size.trim().parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()
+        .map_err(|error| error.to_string())?;
+
+    if let [width, height, k] = sizes[..] {
+        if let Some(board) = MnkBoard::new(width, height, k) { return Ok(board); }
     }
+
+    return Err(format!("Expected WIDTH,HEIGHT,K with sides up to {} and K no longer than a side.", tictactoe::mnkboard::MAX_SIDE));
+}
+
+fn choose_players(variant: Variant) -> Players {
+    println!("Should the computer play 'x', 'o', or neither (press 'Enter')?");
+    let input = get_input().to_lowercase();
+
+    return match input.trim() {
+        "x" => Players::new(default_engine(variant), Player::Human),
+        "o" => Players::new(Player::Human, default_engine(variant)),
+        _ => Players::default(),
+    };
+}
+
+/// The variant as written in records and network greetings.
+fn variant_name(variant: Variant, options: &Options) -> String {
+    fn name<G: Notation>(mut game: G, misere: bool) -> String {
+        game.set_misere(misere);
+        return game.variant();
+    }
+
+    return match variant {
+        Variant::Normal => name(Board::new(), options.misere),
+        Variant::Ultimate => name(UltimateBoard::with_rules(options.rules), options.misere),
+        Variant::Cube => name(CubeBoard::new(), options.misere),
+        Variant::Qubic => name(QubicBoard::new(), options.misere),
+        Variant::Mnk => name(options.mnk.clone(), options.misere),
+    };
+}
+
+/// Unknown variants fall back to normal, whose replay then reports the mismatch.
+fn variant_named(name: &str) -> Variant {
+    let base = name.strip_prefix(notation::MISERE).and_then(|rest| rest.strip_prefix(':')).unwrap_or(name);
+
+    return match base.split(':').next() {
+        Some(UltimateBoard::VARIANT) => Variant::Ultimate,
+        Some(CubeBoard::VARIANT) => Variant::Cube,
+        Some(QubicBoard::VARIANT) => Variant::Qubic,
+        Some(MnkBoard::VARIANT) => Variant::Mnk,
+        _ => Variant::Normal,
+    };
+}
+
+fn tournament(variant: Variant, tournament: Tournament) -> Result<(), String> {
+    if tournament.entrants.iter().any(|player| !player.is_computer()) {
+        return Err(String::from("Only computer players can take part in a tournament."));
+    }
+    if let Variant::Mnk = variant {
+        return Err(String::from("Tournaments are played on the fixed-size variants."));
+    }
+
+    println!("Playing {} games of {}...", tournament.games * tournament.entrants.len() * tournament.entrants.len().saturating_sub(1) / 2, variant_name(variant, &Options::default()));
+
+    let standings = match variant {
+        Variant::Normal => tournament.play::<Board>(),
+        Variant::Ultimate => tournament.play::<UltimateBoard>(),
+        Variant::Cube => tournament.play::<CubeBoard>(),
+        Variant::Qubic => tournament.play::<QubicBoard>(),
+        Variant::Mnk => unreachable!(),
+    };
+
+    println!("{}", standings);
+    return Ok(());
+}
+
+/// Seats `player` on `side` and the other end of the connection opposite them.
+fn seat(side: Piece, player: Player, connection: Connection) -> Players {
+    return match side {
+        Piece::O => Players::new(Player::remote(connection), player),
+        _ => Players::new(player, Player::remote(connection)),
+    };
+}
+
+fn host(port: u16, name: &str, side: Side, player: Player, classic: bool) -> Result<(), String> {
+    let side = match side {
+        Side::X => Piece::X,
+        Side::O => Piece::O,
+    };
+
+    println!("Waiting for the other player on port {}...", port);
+    let mut connection = Connection::host(port).map_err(|error| error.to_string())?;
+    connection.greet(name, side).map_err(|error| error.to_string())?;
+
+    return play(name, &seat(side, player, connection), classic);
+}
+
+fn join(address: &str, player: Player, classic: bool) -> Result<(), String> {
+    let mut connection = Connection::join(address).map_err(|error| error.to_string())?;
+    let (name, side) = connection.await_greeting().map_err(|error| error.to_string())?;
+
+    return play(&name, &seat(side, player, connection), classic);
+}
+
+fn replay(file: &str) -> RecordResult<()> {
+    let record = GameRecord::parse(&std::fs::read_to_string(file)?)?;
+    println!("{} ({}) vs {} ({})", record.x, Piece::X.to_colored_string(), record.o, Piece::O.to_colored_string());
+
+    return match variant_named(&record.variant) {
+        Variant::Normal => step_through::<Board>(&record, Board::print),
+        Variant::Ultimate => step_through::<UltimateBoard>(&record, UltimateBoard::print),
+        Variant::Cube => step_through::<CubeBoard>(&record, CubeBoard::print),
+        Variant::Qubic => step_through::<QubicBoard>(&record, QubicBoard::print),
+        Variant::Mnk => step_through::<MnkBoard>(&record, MnkBoard::print),
+    };
+}
+
+fn step_through<G: Notation>(record: &GameRecord, print: fn(&G)) -> RecordResult<()> {
+    let moves = record.replay::<G>()?.history();
+    let mut board = G::from_variant(&record.variant).unwrap();
+
+    print(&board);
+    for (i, mv) in moves.into_iter().enumerate() {
+        println!("Press 'Enter' for the next move, 'q' to stop.");
+        if get_input().trim() == "q" { return Ok(()); }
+
+        let piece = board.side_to_move();
+        board.apply(mv).unwrap();
+
+        print(&board);
+        println!("{}. ({}) {}", i / 2 + 1, piece.to_colored_string(), G::format_move(mv));
+    }
+
+    println!("Result: {:?}", board.outcome());
+    return Ok(());
+}
+
+fn analyze(file: &str, engine: Option<Player>, time: Duration) -> RecordResult<()> {
+    let record = GameRecord::parse(&std::fs::read_to_string(file)?)?;
+
+    let variant = variant_named(&record.variant);
+    let engine = engine.unwrap_or_else(|| default_engine(variant));
+
+    return match variant {
+        Variant::Normal => suggest::<Board>(&record, &engine, time, Board::print),
+        Variant::Ultimate => suggest::<UltimateBoard>(&record, &engine, time, UltimateBoard::print),
+        Variant::Cube => suggest::<CubeBoard>(&record, &engine, time, CubeBoard::print),
+        Variant::Qubic => suggest::<QubicBoard>(&record, &engine, time, QubicBoard::print),
+        Variant::Mnk => suggest::<MnkBoard>(&record, &engine, time, MnkBoard::print),
+    };
+}
+
+fn suggest<G: Notation>(record: &GameRecord, engine: &Player, time: Duration, print: fn(&G)) -> RecordResult<()> {
+    let board = record.replay::<G>()?;
+    print_analysis(&board, time);
+    print(&board);
+
+    match engine.choose_move(&board) {
+        Some(mv) => println!("({}) {} suggests {}", board.side_to_move().to_colored_string(), engine, G::format_move(mv)),
+        None => println!("The game is over: {:?}", board.outcome()),
+    }
+
+    return Ok(());
+}
+
+/// Lists every move played in `game` with how much it gave away, spending at
+/// most twice `time` on each position.
+fn print_analysis<G: Notation>(game: &G, time: Duration) {
+    println!("{}", "Analyzing...".magenta());
+
+    for (i, annotation) in solver::analyze(game, time).into_iter().enumerate() {
+        let quality = match annotation.quality() {
+            Some(Quality::Best) => "best".green(),
+            Some(Quality::Inaccuracy) => "inaccuracy".yellow().bold(),
+            Some(Quality::Blunder) => "blunder".red().bold(),
+            Some(Quality::Dubious) => "dubious".yellow(),
+            None => "?".dimmed(),
+        };
+
+        println!(
+            "{:>3}. ({}) {:<6} {} ({} -> {})",
+            i / 2 + 1,
+            annotation.side.to_colored_string(),
+            G::format_move(annotation.mv),
+            quality,
+            standing(annotation.before, annotation.chances_before),
+            standing(annotation.after, annotation.chances_after),
+        );
+    }
+}
+
+/// The verdict, or the estimated chances when it's unclear, e.g. `~60%`.
+fn standing(verdict: Verdict, chances: f64) -> String {
+    return match verdict {
+        Verdict::Unclear => format!("~{:.0}%", chances * 100.0),
+        verdict => verdict.to_string(),
+    };
 }
 
 #[test]
@@ -84,4 +470,3 @@ fn test() {
         BoardSelection::Selected(s) => println!("Selected board {}", s),
     }
 }
-
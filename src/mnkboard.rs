@@ -0,0 +1,246 @@
+use crate::board::{BoardError, EndGame};
+use crate::game::Game;
+use crate::utils::Piece;
+
+// Constants
+
+/// Rows are lettered, so a board can't be taller (or, to keep it square-ish, wider) than the alphabet.
+pub const MAX_SIDE: usize = 26;
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+// Type Definitions
+
+/// An m,n,k-game: `width` by `height` spaces, won with `k` in a row.
+#[derive(Clone)]
+pub struct MnkBoard {
+    width: usize,
+    height: usize,
+    k: usize,
+    spaces: Vec<Piece>,
+    lines: Vec<Vec<usize>>,
+    turn: Piece,
+    history: Vec<usize>,
+    undone: Vec<usize>,
+    /// Completing a line loses instead of wins.
+    misere: bool,
+}
+
+// Type Implementations
+
+impl Default for MnkBoard {
+    fn default() -> Self { Self::new(3, 3, 3).unwrap() }
+}
+
+impl MnkBoard {
+    /// `None` if the board is empty, larger than `MAX_SIDE`, or `k` can't fit on it.
+    pub fn new(width: usize, height: usize, k: usize) -> Option<Self> {
+        if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE { return None; }
+        if k == 0 || k > width.max(height) { return None; }
+
+        return Some(Self {
+            width,
+            height,
+            k,
+            spaces: vec![Piece::Empty; width * height],
+            lines: win_lines(width, height, k),
+            turn: Piece::X,
+            history: Vec::new(),
+            undone: Vec::new(),
+            misere: false,
+        });
+    }
+
+    pub fn width(&self) -> usize { self.width }
+
+    pub fn height(&self) -> usize { self.height }
+
+    pub fn k(&self) -> usize { self.k }
+
+    pub fn index(&self, row: usize, col: usize) -> Option<usize> {
+        if row >= self.height || col >= self.width { return None; }
+
+        return Some(row * self.width + col);
+    }
+
+    /// Whose piece is at (row, column), or `Empty` off the board.
+    pub fn get(&self, row: usize, col: usize) -> Piece {
+        return self.index(row, col).map_or(Piece::Empty, |index| self.spaces[index]);
+    }
+
+    pub fn coords(&self, index: usize) -> (usize, usize) { (index / self.width, index % self.width) }
+
+    pub fn play(&mut self, space: usize) -> Result<(), BoardError> {
+        if space >= self.spaces.len() { return Err(BoardError::OutOfBoundsError); }
+
+        match self.spaces[space] {
+            Piece::Empty => { self.spaces[space] = self.turn; },
+            _ => { return Err(BoardError::SpaceTakenError); },
+        }
+
+        return Ok(());
+    }
+
+    fn make_move(&mut self, space: usize) -> Result<(), BoardError> {
+        if self.is_over() { return Err(BoardError::GameOverError); }
+
+        self.play(space)?;
+        self.history.push(space);
+        self.next_turn();
+
+        return Ok(());
+    }
+
+    pub fn get_turn(&self) -> Piece { self.turn }
+
+    pub fn is_misere(&self) -> bool { self.misere }
+
+    pub fn set_misere(&mut self, misere: bool) { self.misere = misere; }
+
+    pub fn next_turn(&mut self) { self.turn = self.turn.opponent(); }
+
+    /// The (row, column) of every empty space, or nothing once the game is over.
+    pub fn legal_moves(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let over = !matches!(self.win_check(), EndGame::NotDone);
+
+        return (0..self.spaces.len())
+            .filter(move |&i| !over && matches!(self.spaces[i], Piece::Empty))
+            .map(|i| self.coords(i));
+    }
+
+    pub fn win_check(&self) -> EndGame { self.line_check().reverse_if(self.misere) }
+
+    /// Who has a line, whatever that means for the result.
+    fn line_check(&self) -> EndGame {
+        for line in &self.lines {
+            let first = self.spaces[line[0]];
+            if first != Piece::Empty && line.iter().all(|&i| self.spaces[i] == first) {
+                return EndGame::Winner(first);
+            }
+        }
+
+        if self.spaces.iter().all(|&space| space != Piece::Empty) { return EndGame::CatsGame; }
+
+        return EndGame::NotDone;
+    }
+
+    pub fn print(&self) {
+        let mut display = String::from("     ");
+        for col in 0..self.width {
+            display.push_str(&format!(" {:^3}", col + 1));
+        }
+        display.push('\n');
+
+        for row in 0..self.height {
+            display.push_str(&format!(" {:>3}  ", (b'A' + row as u8) as char));
+            for col in 0..self.width {
+                if col > 0 { display.push('|'); }
+                display.push_str(&format!(" {} ", self.spaces[row * self.width + col].to_colored_string()));
+            }
+            display.push('\n');
+
+            if row + 1 < self.height {
+                display.push_str("      ");
+                display.push_str(&vec!["---"; self.width].join("+"));
+                display.push('\n');
+            }
+        }
+
+        println!("{display}");
+    }
+}
+
+impl Game for MnkBoard {
+    /// (row, column)
+    type Move = (usize, usize);
+    type Error = BoardError;
+
+    fn side_to_move(&self) -> Piece { self.turn }
+
+    fn legal_moves(&self) -> Vec<(usize, usize)> { self.legal_moves().collect() }
+
+    fn apply(&mut self, (row, col): (usize, usize)) -> Result<(), BoardError> {
+        let index = self.index(row, col).ok_or(BoardError::OutOfBoundsError)?;
+        self.make_move(index)?;
+        self.undone.clear();
+
+        return Ok(());
+    }
+
+    fn undo(&mut self) -> Option<(usize, usize)> {
+        let index = self.history.pop()?;
+        self.spaces[index] = Piece::Empty;
+        self.next_turn();
+        self.undone.push(index);
+
+        return Some(self.coords(index));
+    }
+
+    fn redo(&mut self) -> Option<(usize, usize)> {
+        let index = self.undone.pop()?;
+        self.make_move(index).unwrap();
+
+        return Some(self.coords(index));
+    }
+
+    fn history(&self) -> Vec<(usize, usize)> {
+        return self.history.iter().map(|&index| self.coords(index)).collect();
+    }
+
+    fn outcome(&self) -> EndGame { self.win_check() }
+
+    fn winning_lines(&self) -> Vec<Vec<(usize, usize)>> {
+        return self.lines
+            .iter()
+            .filter(|line| self.spaces[line[0]] != Piece::Empty && line.iter().all(|&i| self.spaces[i] == self.spaces[line[0]]))
+            .map(|line| line.iter().map(|&i| self.coords(i)).collect())
+            .collect();
+    }
+}
+
+// Functions
+
+/// Every run of `k` spaces in a row, column or diagonal.
+fn win_lines(width: usize, height: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut lines = Vec::new();
+
+    for row in 0..height as isize {
+        for col in 0..width as isize {
+            for (dr, dc) in DIRECTIONS {
+                let end_row = row + dr * (k as isize - 1);
+                let end_col = col + dc * (k as isize - 1);
+                if end_row >= height as isize || end_col < 0 || end_col >= width as isize { continue; }
+
+                lines.push(
+                    (0..k as isize)
+                        .map(|i| ((row + dr * i) * width as isize + col + dc * i) as usize)
+                        .collect()
+                );
+            }
+        }
+    }
+
+    return lines;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_counts() {
+        assert_eq!(win_lines(3, 3, 3).len(), 8);
+        assert_eq!(win_lines(4, 4, 4).len(), 10);
+        assert_eq!(win_lines(15, 15, 5).len(), 2 * 15 * 11 + 2 * 11 * 11);
+    }
+
+    #[test]
+    fn connect_four_diagonal() {
+        let mut board = MnkBoard::new(5, 5, 4).unwrap();
+        for mv in [(0, 3), (0, 0), (1, 2), (0, 1), (2, 1), (4, 4), (3, 0)] {
+            board.apply(mv).unwrap();
+        }
+
+        assert_eq!(board.win_check(), EndGame::Winner(Piece::X));
+        assert_eq!(board.legal_moves().count(), 0);
+    }
+}
@@ -0,0 +1,11 @@
+use crate::basic_game;
+use crate::mnkboard::MnkBoard;
+use crate::player::Players;
+use crate::utils::LoopState;
+
+/// Runs one turn, asking a human for a move or letting the computer make one.
+pub fn run(board: &mut MnkBoard, players: &Players) -> LoopState {
+    let welcome = format!("Welcome to {}-in-a-row on {}x{}!", board.k(), board.width(), board.height());
+
+    return basic_game::turn(board, players, MnkBoard::print, &welcome);
+}
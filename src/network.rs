@@ -0,0 +1,182 @@
+//! Line-based protocol for playing over TCP. The host sends
+//! `HELLO <variant> <host's piece>` once the other side connects, then both
+//! sides send `MOVE <move>` for each of their moves, using the variant's
+//! notation, and `BYE` when they leave.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::utils::Piece;
+
+// Constants
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+// Type Definitions
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Hello { variant: String, piece: Piece },
+    Move(String),
+    Bye,
+}
+
+/// One end of a game between two processes.
+#[derive(Debug)]
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+// Type Implementations
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Message::Hello { variant, piece } => write!(f, "HELLO {} {:?}", variant, piece),
+            Message::Move(mv) => write!(f, "MOVE {}", mv),
+            Message::Bye => write!(f, "BYE"),
+        }
+    }
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+
+        let message = match (words.next()?, words.next(), words.next()) {
+            ("HELLO", Some(variant), Some(piece)) => Message::Hello {
+                variant: String::from(variant),
+                piece: match piece {
+                    "X" => Piece::X,
+                    "O" => Piece::O,
+                    _ => { return None; },
+                },
+            },
+            ("MOVE", Some(mv), None) => Message::Move(String::from(mv)),
+            ("BYE", None, None) => Message::Bye,
+            _ => { return None; },
+        };
+
+        if words.next().is_some() { return None; }
+
+        return Some(message);
+    }
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        return Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        });
+    }
+
+    /// Waits on `port` until the other player joins.
+    pub fn host(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        return Self::accept(&listener);
+    }
+
+    pub fn accept(listener: &TcpListener) -> io::Result<Self> {
+        let (stream, _) = listener.accept()?;
+        return Self::new(stream);
+    }
+
+    pub fn join<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        return Self::new(TcpStream::connect(address)?);
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        writeln!(self.writer, "{}", message)?;
+        return self.writer.flush();
+    }
+
+    /// Blocks until the next message arrives. A closed connection is an `UnexpectedEof` error.
+    pub fn receive(&mut self) -> io::Result<Message> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The other player disconnected!"));
+        }
+
+        return Message::parse(line.trim())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Bad message: {}", line.trim())));
+    }
+
+    /// Tells the joining player which variant is being played and who they are.
+    pub fn greet(&mut self, variant: &str, host: Piece) -> io::Result<()> {
+        return self.send(&Message::Hello { variant: String::from(variant), piece: host });
+    }
+
+    /// Waits for the host's greeting, returning the variant and the piece this side plays.
+    pub fn await_greeting(&mut self) -> io::Result<(String, Piece)> {
+        return match self.receive()? {
+            Message::Hello { variant, piece } => Ok((variant, piece.opponent())),
+            message => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Expected HELLO, got {}", message))),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use crate::game::Game;
+    use crate::player::{Player, Players};
+    use crate::ultiboard::UltimateBoard;
+    use crate::utils::LoopState;
+
+    #[test]
+    fn messages_round_trip() {
+        for message in [
+            Message::Hello { variant: String::from("mnk:15:15:5"), piece: Piece::O },
+            Message::Move(String::from("b2a1")),
+            Message::Bye,
+        ] {
+            assert_eq!(Message::parse(&message.to_string()), Some(message));
+        }
+        assert_eq!(Message::parse("MOVE"), None);
+    }
+
+    #[test]
+    fn greeting_over_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let joiner = thread::spawn(move || {
+            let mut connection = Connection::join(address).unwrap();
+            let greeting = connection.await_greeting().unwrap();
+            connection.send(&Message::Move(String::from("a1"))).unwrap();
+            greeting
+        });
+
+        let mut host = Connection::accept(&listener).unwrap();
+        host.greet("ultimate", Piece::X).unwrap();
+
+        assert_eq!(host.receive().unwrap(), Message::Move(String::from("a1")));
+        assert_eq!(joiner.join().unwrap(), (String::from("ultimate"), Piece::O));
+        assert_eq!(host.receive().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn moves_reach_the_other_side() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let joiner = thread::spawn(move || {
+            let players = Players::new(Player::remote(Connection::join(address).unwrap()), Player::Human);
+            let mut board = UltimateBoard::new();
+            assert!(matches!(players.play_turn(&mut board), LoopState::Continue));
+            board.history()
+        });
+
+        let players = Players::new(Player::Human, Player::remote(Connection::accept(&listener).unwrap()));
+        let mut board = UltimateBoard::new();
+        board.apply((4, 0)).unwrap();
+        players.announce::<UltimateBoard>(Piece::X, (4, 0));
+        players.hang_up();
+
+        assert_eq!(joiner.join().unwrap(), vec![(4, 0)]);
+    }
+}
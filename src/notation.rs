@@ -0,0 +1,185 @@
+use crate::board::Board;
+use crate::cubegame::{cubeboard::CubeBoard, qubicboard::{self, QubicBoard}};
+use crate::game::Game;
+use crate::mnkboard::MnkBoard;
+use crate::ultiboard::{Rules, UltimateBoard};
+
+/// Written before a variant's name when completing a line loses, e.g. `misere:cube`.
+pub const MISERE: &str = "misere";
+
+/// Text form of a variant's moves, matching what the game loops accept.
+pub trait Notation: Game + Default {
+    /// Name of the variant, as written in game records.
+    const VARIANT: &'static str;
+
+    /// The variant as written in this game's record, including misère.
+    fn variant(&self) -> String {
+        let base = self.base_variant();
+        return if self.is_misere() { format!("{}:{}", MISERE, base) } else { base };
+    }
+
+    /// A new game for a record's variant, or `None` if it's a different one.
+    fn from_variant(variant: &str) -> Option<Self> {
+        let (misere, base) = match variant.split_once(':') {
+            Some((MISERE, base)) => (true, base),
+            _ => (false, variant),
+        };
+
+        let mut game = Self::from_base_variant(base)?;
+        game.set_misere(misere);
+        return Some(game);
+    }
+
+    /// The variant without misère, for variants whose records also need the
+    /// board's settings.
+    fn base_variant(&self) -> String { String::from(Self::VARIANT) }
+
+    fn from_base_variant(variant: &str) -> Option<Self> {
+        return if variant == Self::VARIANT { Some(Self::default()) } else { None };
+    }
+
+    fn is_misere(&self) -> bool;
+
+    fn set_misere(&mut self, misere: bool);
+
+    fn format_move(mv: Self::Move) -> String;
+
+    fn parse_move(input: &str) -> Option<Self::Move>;
+}
+
+/// Turns a square such as `b3` or `c12` into its zero-based (row, column).
+pub fn square_to_coords(input: &str) -> Option<(usize, usize)> {
+    let row = input.chars().next()?.to_ascii_lowercase();
+    let col: usize = input.get(1..)?.parse().ok()?;
+
+    if !row.is_ascii_lowercase() || col == 0 || !input[1..].chars().all(|c| c.is_ascii_digit()) { return None; }
+
+    return Some((row as usize - 'a' as usize, col - 1));
+}
+
+pub fn coords_to_square(row: usize, col: usize) -> String {
+    return format!("{}{}", (b'a' + row as u8) as char, col + 1);
+}
+
+/// Turns a square such as `b3` into its index on a 3x3 board.
+pub fn square_to_index(input: &str) -> Option<usize> {
+    let (row, col) = square_to_coords(input)?;
+    if row > 2 || col > 2 { return None; }
+
+    return Some(row * 3 + col);
+}
+
+pub fn index_to_square(index: usize) -> String { coords_to_square(index / 3, index % 3) }
+
+impl Notation for Board {
+    const VARIANT: &'static str = "normal";
+
+    fn is_misere(&self) -> bool { self.is_misere() }
+
+    fn set_misere(&mut self, misere: bool) { self.set_misere(misere); }
+
+    fn format_move(mv: usize) -> String { index_to_square(mv) }
+
+    fn parse_move(input: &str) -> Option<usize> { square_to_index(input) }
+}
+
+impl Notation for MnkBoard {
+    const VARIANT: &'static str = "mnk";
+
+    fn is_misere(&self) -> bool { self.is_misere() }
+
+    fn set_misere(&mut self, misere: bool) { self.set_misere(misere); }
+
+    /// Written as `mnk:<width>:<height>:<k>`.
+    fn base_variant(&self) -> String {
+        return format!("{}:{}:{}:{}", Self::VARIANT, self.width(), self.height(), self.k());
+    }
+
+    fn from_base_variant(variant: &str) -> Option<Self> {
+        let mut parts = variant.split(':');
+        if parts.next()? != Self::VARIANT { return None; }
+
+        let mut next = || parts.next()?.parse::<usize>().ok();
+        return MnkBoard::new(next()?, next()?, next()?);
+    }
+
+    fn format_move((row, col): (usize, usize)) -> String { coords_to_square(row, col) }
+
+    fn parse_move(input: &str) -> Option<(usize, usize)> { square_to_coords(input) }
+}
+
+impl Notation for UltimateBoard {
+    const VARIANT: &'static str = "ultimate";
+
+    fn is_misere(&self) -> bool { self.is_misere() }
+
+    fn set_misere(&mut self, misere: bool) { self.set_misere(misere); }
+
+    /// Written as `ultimate`, or `ultimate:<rules>` with house rules, e.g. `ultimate:both,majority`.
+    fn base_variant(&self) -> String {
+        let rules = self.rules();
+        return if rules == Rules::default() { String::from(Self::VARIANT) } else { format!("{}:{}", Self::VARIANT, rules) };
+    }
+
+    fn from_base_variant(variant: &str) -> Option<Self> {
+        return match variant.split_once(':') {
+            Some((Self::VARIANT, rules)) => Some(UltimateBoard::with_rules(rules.parse().ok()?)),
+            _ if variant == Self::VARIANT => Some(UltimateBoard::new()),
+            _ => None,
+        };
+    }
+
+    /// The board followed by the space, e.g. `b2a1`.
+    fn format_move((board, space): (usize, usize)) -> String {
+        return format!("{}{}", index_to_square(board), index_to_square(space));
+    }
+
+    fn parse_move(input: &str) -> Option<(usize, usize)> {
+        if input.len() != 4 { return None; }
+
+        return Some((square_to_index(input.get(..2)?)?, square_to_index(input.get(2..)?)?));
+    }
+}
+
+impl Notation for CubeBoard {
+    const VARIANT: &'static str = "cube";
+
+    fn is_misere(&self) -> bool { self.is_misere() }
+
+    fn set_misere(&mut self, misere: bool) { self.set_misere(misere); }
+
+    /// The layer followed by the space, e.g. `xa1`.
+    fn format_move((layer, index): (usize, usize)) -> String {
+        return format!("{}{}", (b'x' + layer as u8) as char, index_to_square(index));
+    }
+
+    fn parse_move(input: &str) -> Option<(usize, usize)> {
+        let layer = input.chars().next()?.to_ascii_lowercase();
+        if !('x'..='z').contains(&layer) { return None; }
+
+        return Some((layer as usize - 'x' as usize, square_to_index(input.get(1..)?)?));
+    }
+}
+
+impl Notation for QubicBoard {
+    const VARIANT: &'static str = "qubic";
+
+    fn is_misere(&self) -> bool { self.is_misere() }
+
+    fn set_misere(&mut self, misere: bool) { self.set_misere(misere); }
+
+    /// The layer followed by the space, from `wa1` to `zd4`.
+    fn format_move((layer, index): (usize, usize)) -> String {
+        return format!("{}{}", (b'w' + layer as u8) as char, coords_to_square(index / qubicboard::SIZE, index % qubicboard::SIZE));
+    }
+
+    fn parse_move(input: &str) -> Option<(usize, usize)> {
+        let layer = input.chars().next()?.to_ascii_lowercase();
+        if !('w'..='z').contains(&layer) { return None; }
+
+        let (row, col) = square_to_coords(input.get(1..)?)?;
+        if row >= qubicboard::SIZE || col >= qubicboard::SIZE { return None; }
+
+        return Some((layer as usize - 'w' as usize, row * qubicboard::SIZE + col));
+    }
+}
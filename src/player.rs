@@ -0,0 +1,238 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::ai::mcts::{Budget, Mcts};
+use crate::ai::{baseline, negamax};
+use crate::board::Board;
+use crate::engine::Client;
+use crate::network::{Connection, Message};
+use crate::notation::Notation;
+use crate::utils::{LoopState, Piece};
+
+// Type Definitions
+
+/// Who makes the moves for one side.
+#[derive(Debug, Clone)]
+pub enum Player {
+    Human,
+    /// Alpha-beta negamax searching this many plies.
+    Negamax(usize),
+    Mcts(Mcts),
+    /// Plays any legal move.
+    Random,
+    /// Takes immediate wins and avoids immediate losses, otherwise plays randomly.
+    Greedy,
+    /// Someone playing from another process, whose moves arrive over the connection.
+    Remote(Arc<Mutex<Connection>>),
+    /// Another program speaking the engine protocol, started the first time it has to move.
+    External {
+        command: String,
+        client: Arc<Mutex<Option<Client>>>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Players {
+    pub x: Player,
+    pub o: Player,
+}
+
+#[derive(Debug)]
+pub enum PlayerError {
+    UnknownPlayerError(String),
+}
+
+// Type Implementations
+
+impl fmt::Display for PlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlayerError::UnknownPlayerError(input) => write!(
+                f,
+                "Unknown player '{}'! Try 'human', 'ai:negamax[:depth]', 'ai:mcts[:iterations|:<seconds>s]', 'ai:random', 'ai:greedy' or 'engine:<command>'.",
+                input,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlayerError {}
+
+impl Player {
+    /// Whether this side's moves come from somewhere other than this keyboard.
+    pub fn is_computer(&self) -> bool { !matches!(self, Player::Human) }
+
+    pub fn remote(connection: Connection) -> Self { Player::Remote(Arc::new(Mutex::new(connection))) }
+
+    pub fn external(command: &str) -> Self {
+        return Player::External { command: String::from(command), client: Arc::new(Mutex::new(None)) };
+    }
+
+    /// The engine used when none is asked for: a full negamax search on the
+    /// normal board, which it solves, and MCTS for the bigger variants.
+    pub fn default_for(variant: &str) -> Self {
+        return if variant == Board::VARIANT { Player::Negamax(negamax::FULL_DEPTH) } else { Player::Mcts(Mcts::default()) };
+    }
+
+    /// Picks a move for a computer player, or waits for a remote one. `None`
+    /// for humans, once the game is over, or if the remote player left.
+    pub fn choose_move<G: Notation>(&self, game: &G) -> Option<G::Move> {
+        return match self {
+            Player::Human => None,
+            Player::Negamax(depth) => negamax::best_move(game, *depth).map(|(mv, _)| mv),
+            Player::Mcts(engine) => engine.best_move(game),
+            Player::Random => baseline::random_move(game, &mut rand::thread_rng()),
+            Player::Greedy => baseline::greedy_move(game, &mut rand::thread_rng()),
+            Player::Remote(connection) => match connection.lock().ok()?.receive() {
+                Ok(Message::Move(mv)) => G::parse_move(&mv),
+                _ => None,
+            },
+            Player::External { command, client } => {
+                let mut client = client.lock().ok()?;
+                if client.is_none() { *client = Client::spawn(command).ok(); }
+
+                client.as_mut()?.genmove(game).ok()
+            },
+        };
+    }
+}
+
+impl FromStr for Player {
+    type Err = PlayerError;
+
+    /// `human`, `ai:negamax`, `ai:negamax:<depth>`, `ai:mcts`,
+    /// `ai:mcts:<iterations>`, `ai:mcts:<seconds>s`, `ai:random`, `ai:greedy`
+    /// or `engine:<command>`.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || PlayerError::UnknownPlayerError(String::from(input));
+
+        if let Some(command) = input.trim().strip_prefix("engine:") {
+            if command.trim().is_empty() { return Err(error()); }
+            return Ok(Player::external(command.trim()));
+        }
+        let input = input.trim().to_lowercase();
+
+        let player = match input.split(':').collect::<Vec<&str>>().as_slice() {
+            ["human"] => Player::Human,
+            ["ai", "negamax"] => Player::Negamax(negamax::FULL_DEPTH),
+            ["ai", "negamax", depth] => Player::Negamax(depth.parse().map_err(|_| error())?),
+            ["ai", "mcts"] => Player::Mcts(Mcts::default()),
+            ["ai", "mcts", budget] => {
+                let budget = match budget.strip_suffix('s') {
+                    Some(seconds) => Budget::Time(
+                        seconds.parse().ok().and_then(|s| Duration::try_from_secs_f64(s).ok()).ok_or_else(error)?
+                    ),
+                    None => Budget::Iterations(budget.parse().map_err(|_| error())?),
+                };
+                // With nothing to spend the search never looks at a move
+                if let Budget::Iterations(0) = budget { return Err(error()); }
+                if let Budget::Time(time) = budget { if time.is_zero() { return Err(error()); } }
+                Player::Mcts(Mcts::new(budget))
+            },
+            ["ai", "random"] => Player::Random,
+            ["ai", "greedy"] => Player::Greedy,
+            _ => return Err(error()),
+        };
+
+        return Ok(player);
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Player::Human => write!(f, "human"),
+            Player::Negamax(depth) => write!(f, "ai:negamax:{}", depth),
+            Player::Mcts(engine) => match engine.budget {
+                Budget::Iterations(iterations) => write!(f, "ai:mcts:{}", iterations),
+                Budget::Time(time) => write!(f, "ai:mcts:{}s", time.as_secs_f64()),
+            },
+            Player::Random => write!(f, "ai:random"),
+            Player::Greedy => write!(f, "ai:greedy"),
+            Player::Remote(_) => write!(f, "remote"),
+            Player::External { command, .. } => write!(f, "engine:{}", command),
+        }
+    }
+}
+
+impl Default for Players {
+    fn default() -> Self { Self::new(Player::Human, Player::Human) }
+}
+
+impl Players {
+    pub fn new(x: Player, o: Player) -> Self { Self { x, o } }
+
+    pub fn get(&self, piece: Piece) -> &Player {
+        return match piece {
+            Piece::O => &self.o,
+            _ => &self.x,
+        };
+    }
+
+    pub fn is_computer(&self, piece: Piece) -> bool { self.get(piece).is_computer() }
+
+    /// Whether either side is playing from another process, in which case
+    /// moves can't be taken back or games loaded without the two drifting apart.
+    pub fn is_networked(&self) -> bool {
+        return matches!(self.x, Player::Remote(_)) || matches!(self.o, Player::Remote(_));
+    }
+
+    /// Lets the computer or remote player to move make their move, passing it
+    /// on to the other side if that one is remote. `LoopState::Exit` if there
+    /// was no move to make, e.g. because the remote player left.
+    pub fn play_turn<G: Notation>(&self, game: &mut G) -> LoopState {
+        let side = game.side_to_move();
+        let Some(mv) = self.get(side).choose_move(game) else {
+            match self.get(side) {
+                Player::Remote(_) => println!("The other player left!"),
+                Player::External { command, .. } => println!("The engine '{}' didn't answer!", command),
+                _ => { },
+            }
+            return LoopState::Exit;
+        };
+
+        if let Err(error) = game.apply(mv) {
+            println!("{}", error);
+            return LoopState::Exit;
+        }
+        self.announce::<G>(side, mv);
+
+        return LoopState::Continue;
+    }
+
+    /// Sends a move made on this side to the opponent, if they're remote. A
+    /// failed send shows up as a disconnection when waiting for their reply.
+    pub fn announce<G: Notation>(&self, mover: Piece, mv: G::Move) {
+        if let Player::Remote(connection) = self.get(mover.opponent()) {
+            if let Ok(mut connection) = connection.lock() {
+                let _ = connection.send(&Message::Move(G::format_move(mv)));
+            }
+        }
+    }
+
+    /// Tells any remote player that this side is leaving.
+    pub fn hang_up(&self) {
+        for player in [&self.x, &self.o] {
+            if let Player::Remote(connection) = player {
+                if let Ok(mut connection) = connection.lock() {
+                    let _ = connection.send(&Message::Bye);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mcts_needs_a_budget() {
+        assert!("ai:mcts:0".parse::<Player>().is_err());
+        assert!("ai:mcts:0s".parse::<Player>().is_err());
+        assert!("ai:mcts:0.5s".parse::<Player>().is_ok());
+        assert!("ai:mcts:10".parse::<Player>().is_ok());
+    }
+}
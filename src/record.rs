@@ -0,0 +1,288 @@
+//! Game records, saved as text in a PGN-like format:
+//!
+//! ```text
+//! [Variant "ultimate"]
+//! [X "human"]
+//! [O "ai:mcts:1s"]
+//! [Result "1-0"]
+//! [Line "a1a1 a1b2 a1c3 b2a1 b2b2 b2c3 c3a1 c3b2 c3c3"]
+//!
+//! 1. b2a1 a1c2 2. c2b2 b2c3
+//! ```
+//!
+//! `Variant` is one of `normal`, `ultimate`, `cube`, `qubic` or `mnk:<width>:<height>:<k>`.
+//! Ultimate played with house rules is `ultimate:<rules>`, e.g. `ultimate:both,majority`,
+//! and any variant played in misère has a `misere:` prefix, e.g. `misere:cube`.
+//! `Result` is `1-0` when X won, `0-1` when O won, `1/2-1/2` for a cat's game
+//! and `*` for a game that is still going. There's a `Line` header for each
+//! line the winner completed, listing its cells. Moves use the same notation
+//! as the game loops, with an optional move number before each pair.
+
+use std::fmt;
+use std::fs;
+use std::io;
+
+use crate::board::EndGame;
+use crate::notation::Notation;
+use crate::player::Players;
+use crate::utils::Piece;
+
+// Type Definitions
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    pub variant: String,
+    pub x: String,
+    pub o: String,
+    pub result: EndGame,
+    /// The cells of each winning line.
+    pub lines: Vec<String>,
+    pub moves: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum RecordError {
+    IoError(io::Error),
+    HeaderError(String),
+    VariantError(String),
+    MoveError(String),
+    NetworkError,
+    /// `save` or `load` was typed without a file.
+    MissingPathError(String),
+}
+
+pub type RecordResult<T> = Result<T, RecordError>;
+
+// Type Implementations
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordError::IoError(error) => write!(f, "Could not access the file: {}", error),
+            RecordError::HeaderError(line) => write!(f, "Bad header line: {}", line),
+            RecordError::VariantError(variant) => write!(f, "That record is for a different game: {}", variant),
+            RecordError::MoveError(mv) => write!(f, "Bad move in record: {}", mv),
+            RecordError::NetworkError => write!(f, "Games can't be loaded over the network!"),
+            RecordError::MissingPathError(command) => write!(f, "Which file? Usage: {} <file>", command),
+        }
+    }
+}
+
+impl From<io::Error> for RecordError {
+    fn from(error: io::Error) -> Self { RecordError::IoError(error) }
+}
+
+impl GameRecord {
+    /// Records the moves made so far in `game`.
+    pub fn from_game<G: Notation>(game: &G, x: &str, o: &str) -> Self {
+        Self {
+            variant: game.variant(),
+            x: String::from(x),
+            o: String::from(o),
+            result: game.outcome(),
+            lines: game
+                .winning_lines()
+                .into_iter()
+                .map(|line| line.into_iter().map(G::format_move).collect::<Vec<String>>().join(" "))
+                .collect(),
+            moves: game.history().into_iter().map(G::format_move).collect(),
+        }
+    }
+
+    /// Plays the recorded moves on a fresh board.
+    pub fn replay<G: Notation>(&self) -> RecordResult<G> {
+        let mut game = G::from_variant(&self.variant).ok_or_else(|| RecordError::VariantError(self.variant.clone()))?;
+
+        for mv in &self.moves {
+            let parsed = G::parse_move(mv).ok_or_else(|| RecordError::MoveError(mv.clone()))?;
+            game.apply(parsed).map_err(|_| RecordError::MoveError(mv.clone()))?;
+        }
+
+        return Ok(game);
+    }
+
+    pub fn parse(input: &str) -> RecordResult<Self> {
+        let mut record = Self {
+            variant: String::new(),
+            x: String::new(),
+            o: String::new(),
+            result: EndGame::NotDone,
+            lines: Vec::new(),
+            moves: Vec::new(),
+        };
+
+        for line in input.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line.starts_with('[') {
+                let (key, value) = parse_header(line).ok_or_else(|| RecordError::HeaderError(String::from(line)))?;
+
+                match key {
+                    "Variant" => { record.variant = value; },
+                    "X" => { record.x = value; },
+                    "O" => { record.o = value; },
+                    "Line" => { record.lines.push(value); },
+                    "Result" => {
+                        record.result = result_from_str(&value).ok_or_else(|| RecordError::HeaderError(String::from(line)))?;
+                    },
+                    _ => { },
+                }
+                continue;
+            }
+
+            for token in line.split_whitespace() {
+                if token.ends_with('.') { continue; }
+                record.moves.push(String::from(token));
+            }
+        }
+
+        if record.variant.is_empty() { return Err(RecordError::HeaderError(String::from("missing Variant"))); }
+
+        return Ok(record);
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "[Variant \"{}\"]", self.variant)?;
+        writeln!(f, "[X \"{}\"]", self.x)?;
+        writeln!(f, "[O \"{}\"]", self.o)?;
+        writeln!(f, "[Result \"{}\"]", result_to_str(self.result))?;
+        for line in &self.lines {
+            writeln!(f, "[Line \"{}\"]", line)?;
+        }
+        writeln!(f)?;
+
+        for (i, pair) in self.moves.chunks(2).enumerate() {
+            if i > 0 { write!(f, " ")?; }
+            write!(f, "{}. {}", i + 1, pair.join(" "))?;
+        }
+
+        return writeln!(f);
+    }
+}
+
+// Functions
+
+/// Writes `game` to the file at `path`.
+pub fn save<G: Notation>(game: &G, players: &Players, path: &str) -> RecordResult<()> {
+    let record = GameRecord::from_game(game, &players.x.to_string(), &players.o.to_string());
+
+    fs::write(path, record.to_string())?;
+    return Ok(());
+}
+
+/// Reads the game stored in the file at `path`.
+pub fn load<G: Notation>(path: &str) -> RecordResult<G> {
+    return GameRecord::parse(&fs::read_to_string(path)?)?.replay();
+}
+
+/// Handles `save <file>` and `load <file>` typed into a game loop, returning a
+/// message for the player. `None` if `input` was neither command.
+pub fn run_command<G: Notation>(game: &mut G, players: &Players, input: &str) -> Option<RecordResult<String>> {
+    let input = input.trim();
+    let (command, path) = input.split_once(' ').unwrap_or((input, ""));
+    let path = path.trim();
+
+    if !matches!(command, "save" | "load") { return None; }
+    if path.is_empty() { return Some(Err(RecordError::MissingPathError(String::from(command)))); }
+
+    return match command {
+        "save" => Some(save(game, players, path).map(|_| format!("Saved to {}.", path))),
+        "load" if players.is_networked() => Some(Err(RecordError::NetworkError)),
+        "load" => Some(load(path).map(|loaded| {
+            *game = loaded;
+            format!("Loaded {}.", path)
+        })),
+        _ => None,
+    };
+}
+
+fn parse_header(line: &str) -> Option<(&str, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (key, value) = inner.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    return Some((key, String::from(value)));
+}
+
+/// The `Result` header for `result`.
+pub fn result_to_str(result: EndGame) -> &'static str {
+    return match result {
+        EndGame::Winner(Piece::X) => "1-0",
+        EndGame::Winner(Piece::O) => "0-1",
+        EndGame::CatsGame => "1/2-1/2",
+        _ => "*",
+    };
+}
+
+fn result_from_str(input: &str) -> Option<EndGame> {
+    return match input {
+        "1-0" => Some(EndGame::Winner(Piece::X)),
+        "0-1" => Some(EndGame::Winner(Piece::O)),
+        "1/2-1/2" => Some(EndGame::CatsGame),
+        "*" => Some(EndGame::NotDone),
+        _ => None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+    use crate::ultiboard::UltimateBoard;
+
+    #[test]
+    fn round_trip() {
+        let mut board = UltimateBoard::new();
+        for mv in [(4, 0), (0, 4), (4, 1), (1, 4), (4, 2)] {
+            board.apply(mv).unwrap();
+        }
+
+        let text = GameRecord::from_game(&board, "human", "ai:mcts:1s").to_string();
+        assert!(text.contains("1. b2a1 a1b2 2. b2a2 a2b2 3. b2a3"));
+
+        let record = GameRecord::parse(&text).unwrap();
+        assert_eq!(record.x, "human");
+        assert_eq!(record.o, "ai:mcts:1s");
+
+        let loaded: UltimateBoard = record.replay().unwrap();
+        assert_eq!(loaded.history(), board.history());
+        assert!(record.replay::<crate::board::Board>().is_err());
+        assert!(record.lines.is_empty());
+    }
+
+    #[test]
+    fn stores_the_winning_line() {
+        let mut board = crate::board::Board::new();
+        for mv in [0, 3, 1, 4, 2] {
+            board.apply(mv).unwrap();
+        }
+
+        let text = GameRecord::from_game(&board, "human", "human").to_string();
+        assert!(text.contains("[Line \"a1 a2 a3\"]"));
+        assert_eq!(GameRecord::parse(&text).unwrap().lines, vec![String::from("a1 a2 a3")]);
+    }
+
+    #[test]
+    fn commands_need_a_path() {
+        let mut board = crate::board::Board::new();
+        let players = Players::default();
+
+        assert!(matches!(run_command(&mut board, &players, "save"), Some(Err(RecordError::MissingPathError(_)))));
+        assert!(matches!(run_command(&mut board, &players, "load  "), Some(Err(RecordError::MissingPathError(_)))));
+        assert!(run_command(&mut board, &players, "a1").is_none());
+    }
+
+    #[test]
+    fn keeps_misere_and_house_rules() {
+        let mut board = UltimateBoard::with_rules("both,majority".parse().unwrap());
+        board.set_misere(true);
+
+        let record = GameRecord::parse(&GameRecord::from_game(&board, "human", "human").to_string()).unwrap();
+        assert_eq!(record.variant, "misere:ultimate:both,majority");
+
+        let loaded: UltimateBoard = record.replay().unwrap();
+        assert!(loaded.is_misere());
+        assert_eq!(loaded.rules(), board.rules());
+        assert!(record.replay::<crate::board::Board>().is_err());
+    }
+}
@@ -0,0 +1,237 @@
+//! Rotations and reflections of the boards. Positions that are the same up to
+//! a symmetry play out the same way, so they can share a canonical form in
+//! transposition tables, opening books and archives.
+
+use crate::board::Board;
+use crate::cubegame::cubeboard::CubeBoard;
+use crate::game::Game;
+use crate::ultiboard::{BoardSelection, BoardState, UltimateBoard};
+use crate::utils::Piece;
+
+// Type Definitions
+
+/// One of the symmetries of a board, the identity being `Symmetry(0)`.
+///
+/// On a 3x3 grid the low bit swaps rows and columns, then the next bits flip
+/// the rows and the columns, giving all 8 rotations and reflections. On the
+/// cube `index / 8` picks one of the 6 orders of the axes and each of the low
+/// 3 bits flips one of them, giving all 48.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symmetry(pub usize);
+
+/// A board whose symmetries map legal games onto legal games.
+pub trait Symmetric: Game + Default {
+    /// How many symmetries the board has.
+    const SYMMETRIES: usize;
+
+    /// Where `mv` lands under `symmetry`.
+    fn transform_move(mv: Self::Move, symmetry: Symmetry) -> Self::Move;
+
+    /// Equal for two boards exactly when they hold the same position.
+    fn position_key(&self) -> Vec<u8>;
+}
+
+// Type Implementations
+
+impl Symmetry {
+    pub const IDENTITY: Symmetry = Symmetry(0);
+
+    /// Every symmetry of `G`, the identity first.
+    pub fn all<G: Symmetric>() -> impl Iterator<Item = Symmetry> {
+        return (0..G::SYMMETRIES).map(Symmetry);
+    }
+
+    /// The symmetry that undoes this one.
+    pub fn inverse<G: Symmetric>(self) -> Symmetry {
+        let cells = G::default().legal_moves();
+
+        return Symmetry::all::<G>()
+            .find(|&other| cells.iter().all(|&mv| G::transform_move(G::transform_move(mv, self), other) == mv))
+            .expect("every symmetry has an inverse");
+    }
+}
+
+impl Symmetric for Board {
+    const SYMMETRIES: usize = 8;
+
+    fn transform_move(space: usize, symmetry: Symmetry) -> usize { square(space, symmetry) }
+
+    fn position_key(&self) -> Vec<u8> {
+        return (0..9).map(|space| piece_key(self.get(space))).collect();
+    }
+}
+
+impl Symmetric for UltimateBoard {
+    /// The same symmetry is applied to the sub-boards and to the spaces inside
+    /// them, so the sub-board a move sends the opponent to moves with it.
+    const SYMMETRIES: usize = 8;
+
+    fn transform_move((index, space): (usize, usize), symmetry: Symmetry) -> (usize, usize) {
+        return (square(index, symmetry), square(space, symmetry));
+    }
+
+    fn position_key(&self) -> Vec<u8> {
+        let mut key: Vec<u8> = (0..81).map(|i| piece_key(self.get_space(i / 9, i % 9))).collect();
+        key.push(match self.get_focus() {
+            BoardSelection::Selected(index) => *index as u8,
+            BoardSelection::Unselected => 9,
+        });
+        // Who owns a sub-board depends on which line was finished first
+        key.extend((0..9).map(|b| match self.get_board_state(b) {
+            BoardState::InPlay => 0,
+            BoardState::Winner(piece) => piece_key(piece),
+            BoardState::CatsGame => 3,
+        }));
+
+        return key;
+    }
+}
+
+impl Symmetric for CubeBoard {
+    const SYMMETRIES: usize = 48;
+
+    fn transform_move((layer, index): (usize, usize), symmetry: Symmetry) -> (usize, usize) {
+        // The axes in every order, as a permutation of (layer, row, column)
+        const ORDERS: [[usize; 3]; 6] = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+
+        let at = [layer, index / 3, index % 3];
+        let mut moved = ORDERS[symmetry.0 / 8].map(|axis| at[axis]);
+        for (axis, coord) in moved.iter_mut().enumerate() {
+            if symmetry.0 & (1 << axis) != 0 { *coord = 2 - *coord; }
+        }
+
+        return (moved[0], moved[1] * 3 + moved[2]);
+    }
+
+    fn position_key(&self) -> Vec<u8> {
+        return (0..27).map(|i| piece_key(self.get(i / 9, i % 9))).collect();
+    }
+}
+
+// Functions
+
+/// `game` played out again with every move moved by `symmetry`, keeping its
+/// settings such as misère.
+pub fn transform<G: Symmetric>(game: &G, symmetry: Symmetry) -> G {
+    let moves = game.history();
+    let mut transformed = game.clone();
+    while transformed.undo().is_some() { }
+
+    for mv in moves {
+        transformed.apply(G::transform_move(mv, symmetry)).expect("symmetries keep moves legal");
+    }
+
+    return transformed;
+}
+
+/// The canonical form of `game`, which is the same for every position
+/// equivalent to it, along with the symmetry that takes `game` there.
+/// `symmetry.inverse::<G>()` maps moves found on the canonical board back.
+pub fn canonical<G: Symmetric>(game: &G) -> (G, Symmetry) {
+    return Symmetry::all::<G>()
+        .map(|symmetry| (transform(game, symmetry), symmetry))
+        .min_by_key(|(transformed, _)| transformed.position_key())
+        .expect("the identity is always a symmetry");
+}
+
+/// Where `index` on a 3x3 grid lands under `symmetry`.
+fn square(index: usize, symmetry: Symmetry) -> usize {
+    let (mut row, mut col) = (index / 3, index % 3);
+
+    if symmetry.0 & 1 != 0 { (row, col) = (col, row); }
+    if symmetry.0 & 2 != 0 { row = 2 - row; }
+    if symmetry.0 & 4 != 0 { col = 2 - col; }
+
+    return row * 3 + col;
+}
+
+fn piece_key(piece: Piece) -> u8 {
+    return match piece {
+        Piece::Empty => 0,
+        Piece::X => 1,
+        Piece::O => 2,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symmetries_are_distinct_permutations() {
+        fn check<G: Symmetric>() {
+            let cells = G::default().legal_moves();
+            let mut images = Vec::new();
+
+            for symmetry in Symmetry::all::<G>() {
+                let image: Vec<G::Move> = cells.iter().map(|&mv| G::transform_move(mv, symmetry)).collect();
+                assert!(cells.iter().all(|mv| image.contains(mv)));
+                assert!(!images.contains(&image));
+                images.push(image);
+            }
+            assert_eq!(images[0], cells);
+        }
+
+        check::<Board>();
+        check::<UltimateBoard>();
+        check::<CubeBoard>();
+    }
+
+    #[test]
+    fn corner_openings_share_a_canonical_form() {
+        let canon = |space: usize| {
+            let mut board = Board::new();
+            board.apply(space).unwrap();
+            return canonical(&board);
+        };
+
+        let (corner, _) = canon(0);
+        for space in [2, 6, 8] {
+            let (board, symmetry) = canon(space);
+            assert_eq!(board.position_key(), corner.position_key());
+            assert_eq!(Board::transform_move(space, symmetry), corner.history()[0]);
+            assert_eq!(Board::transform_move(corner.history()[0], symmetry.inverse::<Board>()), space);
+        }
+        assert_ne!(canon(1).0.position_key(), corner.position_key());
+        assert_ne!(canon(4).0.position_key(), corner.position_key());
+    }
+
+    #[test]
+    fn ultimate_keeps_the_target_board() {
+        let mut board = UltimateBoard::new();
+        for mv in [(4, 0), (0, 2)] {
+            board.apply(mv).unwrap();
+        }
+
+        let (canon, symmetry) = canonical(&board);
+        assert_eq!(*canon.get_focus(), BoardSelection::Selected(square(2, symmetry)));
+        assert_eq!(canon.get_space(4, square(0, symmetry)), Piece::X);
+    }
+
+    #[test]
+    fn ultimate_keeps_who_won_each_board() {
+        use crate::ultiboard::Rules;
+
+        // Both players line up on board a1 with board c3 won, so either can be
+        // sent anywhere, including back to a1
+        let opening = [
+            (8, 1), (1, 6), (6, 0), (0, 5), (5, 6), (6, 8), (8, 0), (0, 7), (7, 1), (1, 4), (4, 4),
+            (4, 0), (0, 0), (0, 6), (6, 5), (5, 3), (3, 1), (1, 0), (0, 1), (1, 8), (8, 2), (2, 1),
+        ];
+        let play = |ending: [(usize, usize); 4]| {
+            let mut board = UltimateBoard::with_rules(Rules { choose_any_board: true, ..Rules::default() });
+            for mv in opening.into_iter().chain(ending) {
+                board.apply(mv).unwrap();
+            }
+            return board;
+        };
+
+        // The same moves, with X or O finishing their line on a1 first
+        let x_first = play([(0, 2), (2, 8), (3, 8), (0, 8)]);
+        let o_first = play([(3, 8), (0, 8), (0, 2), (2, 8)]);
+        assert_eq!(x_first.get_board_state(0), BoardState::Winner(Piece::X));
+        assert_eq!(o_first.get_board_state(0), BoardState::Winner(Piece::O));
+        assert_ne!(x_first.position_key(), o_first.position_key());
+        assert_eq!(x_first.position_key()[..82], o_first.position_key()[..82]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Round-robin matches between computer players, with win/draw/loss tables,
+//! Elo estimates and average game length.
+
+use std::fmt;
+
+use crate::board::EndGame;
+use crate::notation::Notation;
+use crate::player::{Player, Players};
+use crate::utils::Piece;
+
+// Constants
+
+/// Rating the Elo estimates are centred on.
+pub const AVERAGE_ELO: f64 = 1500.0;
+const ELO_ITERATIONS: usize = 1000;
+
+// Type Definitions
+
+/// Every entrant plays every other one `games` times, swapping who plays X each game.
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub entrants: Vec<Player>,
+    pub games: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Score {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Standings {
+    pub names: Vec<String>,
+    /// `pairs[i][j]` is how entrant `i` did against entrant `j`.
+    pub pairs: Vec<Vec<Score>>,
+    pub games: usize,
+    /// Moves made across every game.
+    pub plies: usize,
+}
+
+// Type Implementations
+
+impl Score {
+    pub fn games(&self) -> usize { self.wins + self.draws + self.losses }
+
+    /// One point per win and half a point per draw.
+    pub fn points(&self) -> f64 { self.wins as f64 + self.draws as f64 / 2.0 }
+
+    fn add(&mut self, other: Score) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+    }
+}
+
+impl Tournament {
+    pub fn new(entrants: Vec<Player>, games: usize) -> Self { Self { entrants, games } }
+
+    pub fn play<G: Notation>(&self) -> Standings {
+        let count = self.entrants.len();
+        let mut standings = Standings {
+            names: self.entrants.iter().map(Player::to_string).collect(),
+            pairs: vec![vec![Score::default(); count]; count],
+            games: 0,
+            plies: 0,
+        };
+
+        for first in 0..count {
+            for second in first + 1..count {
+                for game in 0..self.games {
+                    let (x, o) = if game % 2 == 0 { (first, second) } else { (second, first) };
+                    let (outcome, plies) = play_game::<G>(&Players::new(self.entrants[x].clone(), self.entrants[o].clone()));
+
+                    standings.record(x, o, outcome);
+                    standings.games += 1;
+                    standings.plies += plies;
+                }
+            }
+        }
+
+        return standings;
+    }
+}
+
+impl Standings {
+    fn record(&mut self, x: usize, o: usize, outcome: EndGame) {
+        match outcome {
+            EndGame::Winner(Piece::X) => {
+                self.pairs[x][o].wins += 1;
+                self.pairs[o][x].losses += 1;
+            },
+            EndGame::Winner(Piece::O) => {
+                self.pairs[o][x].wins += 1;
+                self.pairs[x][o].losses += 1;
+            },
+            _ => {
+                self.pairs[x][o].draws += 1;
+                self.pairs[o][x].draws += 1;
+            },
+        }
+    }
+
+    /// Everything entrant `i` scored against the others.
+    pub fn total(&self, i: usize) -> Score {
+        let mut total = Score::default();
+        for score in &self.pairs[i] { total.add(*score); }
+
+        return total;
+    }
+
+    pub fn average_length(&self) -> f64 {
+        return if self.games == 0 { 0.0 } else { self.plies as f64 / self.games as f64 };
+    }
+
+    /// Ratings that best explain the results, averaging `AVERAGE_ELO`. Every
+    /// pairing counts one extra drawn game, so entrants that won or lost every
+    /// game still get a finite rating.
+    pub fn elo(&self) -> Vec<f64> {
+        let count = self.names.len();
+        let mut ratings = vec![0.0; count];
+
+        for _ in 0..ELO_ITERATIONS {
+            for i in 0..count {
+                let mut games = 0.0;
+                let mut actual = 0.0;
+                let mut expected = 0.0;
+
+                for j in (0..count).filter(|&j| j != i) {
+                    let played = self.pairs[i][j].games() as f64 + 1.0;
+                    games += played;
+                    actual += self.pairs[i][j].points() + 0.5;
+                    expected += played / (1.0 + 10f64.powf((ratings[j] - ratings[i]) / 400.0));
+                }
+
+                if games > 0.0 { ratings[i] += 200.0 * (actual - expected) / games; }
+            }
+        }
+
+        let mean = ratings.iter().sum::<f64>() / count.max(1) as f64;
+        return ratings.into_iter().map(|rating| rating - mean + AVERAGE_ELO).collect();
+    }
+}
+
+impl fmt::Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.names.iter().map(String::len).max().unwrap_or(0).max("Entrant".len());
+        let elo = self.elo();
+
+        let mut order: Vec<usize> = (0..self.names.len()).collect();
+        order.sort_by(|&a, &b| elo[b].total_cmp(&elo[a]));
+
+        writeln!(f, "{:<width$}  {:>5} {:>5} {:>5} {:>7} {:>6}", "Entrant", "W", "D", "L", "Score", "Elo")?;
+        for &i in &order {
+            let total = self.total(i);
+            writeln!(
+                f, "{:<width$}  {:>5} {:>5} {:>5} {:>7.1} {:>6.0}",
+                self.names[i], total.wins, total.draws, total.losses, total.points(), elo[i],
+            )?;
+        }
+
+        writeln!(f)?;
+        write!(f, "{:<width$}", "W-D-L vs", width = width + 3)?;
+        for column in 1..=order.len() { write!(f, "  {:>9}", column)?; }
+        writeln!(f)?;
+
+        for (row, &i) in order.iter().enumerate() {
+            write!(f, "{:<width$}", format!("{}. {}", row + 1, self.names[i]), width = width + 3)?;
+            for &j in &order {
+                let cell = if i == j {
+                    String::from("-")
+                } else {
+                    let score = self.pairs[i][j];
+                    format!("{}-{}-{}", score.wins, score.draws, score.losses)
+                };
+                write!(f, "  {:>9}", cell)?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f)?;
+        return write!(f, "{} games, {:.1} moves on average", self.games, self.average_length());
+    }
+}
+
+// Functions
+
+/// Plays one game to the end, returning the outcome and how many moves were
+/// made. A player that can't come up with a move loses.
+fn play_game<G: Notation>(players: &Players) -> (EndGame, usize) {
+    let mut game = G::default();
+    let mut plies = 0;
+
+    while !game.is_over() {
+        let side = game.side_to_move();
+        match players.get(side).choose_move(&game) {
+            Some(mv) if game.apply(mv).is_ok() => { plies += 1; },
+            _ => { return (EndGame::Winner(side.opponent()), plies); },
+        }
+    }
+
+    return (game.outcome(), plies);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::negamax;
+    use crate::board::Board;
+
+    #[test]
+    fn perfect_play_never_loses() {
+        let tournament = Tournament::new(vec![Player::Negamax(negamax::FULL_DEPTH), Player::Greedy, Player::Random], 4);
+        let standings = tournament.play::<Board>();
+
+        assert_eq!(standings.games, 12);
+        for i in 0..3 {
+            assert_eq!(standings.total(i).games(), 8);
+            for j in 0..3 {
+                assert_eq!(standings.pairs[i][j].wins, standings.pairs[j][i].losses);
+            }
+        }
+        assert_eq!(standings.total(0).losses, 0);
+
+        let elo = standings.elo();
+        assert!(elo[0] >= elo[2]);
+        assert!((elo.iter().sum::<f64>() / 3.0 - AVERAGE_ELO).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,375 @@
+//! Full-screen play: the board is drawn as a grid of cells, a cursor is moved
+//! with the arrow keys or hjkl, and Enter or Space places a piece under it.
+
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind},
+    queue,
+    style::{Color, Print, Stylize},
+    terminal::{self, ClearType},
+};
+
+use crate::ai::solver;
+use crate::board::{Board, EndGame};
+use crate::cubegame::{cubeboard::CubeBoard, qubicboard::{self, QubicBoard}};
+use crate::game;
+use crate::mnkboard::MnkBoard;
+use crate::notation::Notation;
+use crate::player::Players;
+use crate::record;
+use crate::ultiboard::{BoardSelection, BoardState, UltimateBoard};
+use crate::utils::{LoopState, Piece};
+
+// Type Definitions
+
+/// How a variant is laid out as a grid of cells on screen.
+pub trait Layout: Notation {
+    /// Rows and columns of cells.
+    fn size(&self) -> (usize, usize);
+
+    /// Rows and columns in each block of cells, drawn with heavier borders:
+    /// Ultimate's sub-boards or a cube's layers.
+    fn block(&self) -> (usize, usize);
+
+    fn cell(&self, row: usize, col: usize) -> Piece;
+
+    /// Who has claimed the cell at (row, col) without playing on it, like the
+    /// winner of an Ultimate sub-board.
+    fn owner(&self, _row: usize, _col: usize) -> Piece { Piece::Empty }
+
+    /// The (row, col) of the block the next piece has to go in, when play is
+    /// held to one, like the Ultimate sub-board the last move sent it to.
+    fn focused_block(&self) -> Option<(usize, usize)> { None }
+
+    /// The move that places a piece at (row, col).
+    fn to_move(&self, row: usize, col: usize) -> Self::Move;
+}
+
+/// Raw mode on an alternate screen for as long as it's alive, even through a panic.
+struct Screen;
+
+// Type Implementations
+
+impl Screen {
+    fn new() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        queue!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        io::stdout().flush()?;
+
+        return Ok(Screen);
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = queue!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = io::stdout().flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl Layout for Board {
+    fn size(&self) -> (usize, usize) { (3, 3) }
+
+    fn block(&self) -> (usize, usize) { (3, 3) }
+
+    fn cell(&self, row: usize, col: usize) -> Piece { self.get(row * 3 + col) }
+
+    fn to_move(&self, row: usize, col: usize) -> usize { row * 3 + col }
+}
+
+impl Layout for UltimateBoard {
+    fn size(&self) -> (usize, usize) { (9, 9) }
+
+    fn block(&self) -> (usize, usize) { (3, 3) }
+
+    fn cell(&self, row: usize, col: usize) -> Piece {
+        let (board, space) = self.to_move(row, col);
+        return self.get_space(board, space);
+    }
+
+    fn owner(&self, row: usize, col: usize) -> Piece {
+        let (board, _) = self.to_move(row, col);
+        return match self.get_board_state(board) {
+            BoardState::Winner(piece) => piece,
+            _ => Piece::Empty,
+        };
+    }
+
+    fn focused_block(&self) -> Option<(usize, usize)> {
+        return match self.get_focus() {
+            BoardSelection::Selected(board) => Some((board / 3, board % 3)),
+            BoardSelection::Unselected => None,
+        };
+    }
+
+    /// Sub-boards are laid out like the spaces within them.
+    fn to_move(&self, row: usize, col: usize) -> (usize, usize) {
+        return ((row / 3) * 3 + col / 3, (row % 3) * 3 + col % 3);
+    }
+}
+
+/// The layers side by side, X on the left.
+impl Layout for CubeBoard {
+    fn size(&self) -> (usize, usize) { (3, 9) }
+
+    fn block(&self) -> (usize, usize) { (3, 3) }
+
+    fn cell(&self, row: usize, col: usize) -> Piece {
+        let (layer, index) = self.to_move(row, col);
+        return self.get(layer, index);
+    }
+
+    fn to_move(&self, row: usize, col: usize) -> (usize, usize) { (col / 3, row * 3 + col % 3) }
+}
+
+/// The layers side by side, W on the left.
+impl Layout for QubicBoard {
+    fn size(&self) -> (usize, usize) { (qubicboard::SIZE, qubicboard::LAYER_LEN) }
+
+    fn block(&self) -> (usize, usize) { (qubicboard::SIZE, qubicboard::SIZE) }
+
+    fn cell(&self, row: usize, col: usize) -> Piece {
+        let (layer, index) = self.to_move(row, col);
+        return self.get(layer, index);
+    }
+
+    fn to_move(&self, row: usize, col: usize) -> (usize, usize) {
+        return (col / qubicboard::SIZE, row * qubicboard::SIZE + col % qubicboard::SIZE);
+    }
+}
+
+impl Layout for MnkBoard {
+    fn size(&self) -> (usize, usize) { (self.height(), self.width()) }
+
+    fn block(&self) -> (usize, usize) { (self.height(), self.width()) }
+
+    fn cell(&self, row: usize, col: usize) -> Piece { self.get(row, col) }
+
+    fn to_move(&self, row: usize, col: usize) -> (usize, usize) { (row, col) }
+}
+
+// Functions
+
+/// Plays `game` full-screen until it ends or a human quits.
+pub fn play<G: Layout>(game: &mut G, players: &Players) -> io::Result<()> {
+    let _screen = Screen::new()?;
+    let (rows, cols) = game.size();
+    let mut cursor = (rows / 2, cols / 2);
+    let mut status = String::new();
+
+    loop {
+        if game.is_over() {
+            let message = match game.outcome() {
+                EndGame::Winner(piece) => format!("{:?} wins!", piece),
+                _ => String::from("Cat's Game!"),
+            };
+            draw(game, None, &format!("{} Press any key to leave.", message))?;
+            wait_for_key()?;
+            return Ok(());
+        }
+
+        let side = game.side_to_move();
+
+        if players.is_computer(side) {
+            draw(game, None, &format!("({:?}) Thinking...", side))?;
+            if let LoopState::Exit = players.play_turn(game) {
+                draw(game, None, "The game was cut short. Press any key to leave.")?;
+                wait_for_key()?;
+                return Ok(());
+            }
+            continue;
+        }
+
+        let legal = game.legal_moves();
+        if !legal.contains(&game.to_move(cursor.0, cursor.1)) {
+            cursor = nearest_legal(game, cursor, &legal);
+        }
+
+        let hint = format!("({:?}) {} {}", side, G::format_move(game.to_move(cursor.0, cursor.1)), status);
+        draw(game, Some(cursor), &hint)?;
+        status.clear();
+
+        let Event::Key(key) = event::read()? else { continue; };
+        if key.kind != KeyEventKind::Press { continue; }
+
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => { cursor.1 = cursor.1.saturating_sub(1); },
+            KeyCode::Right | KeyCode::Char('l') => { cursor.1 = (cursor.1 + 1).min(cols - 1); },
+            KeyCode::Up | KeyCode::Char('k') => { cursor.0 = cursor.0.saturating_sub(1); },
+            KeyCode::Down | KeyCode::Char('j') => { cursor.0 = (cursor.0 + 1).min(rows - 1); },
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let mv = game.to_move(cursor.0, cursor.1);
+                match game.apply(mv) {
+                    Ok(()) => players.announce::<G>(side, mv),
+                    Err(error) => { status = error.to_string(); },
+                }
+            },
+            KeyCode::Char('u') => game::undo_turn(game, players),
+            KeyCode::Char('r') => game::redo_turn(game, players),
+            KeyCode::Char('?') => {
+                draw(game, Some(cursor), &format!("({:?}) Thinking...", side))?;
+                if let Some((mv, verdict)) = solver::solve(game, solver::HINT_TIME) {
+                    cursor = nearest_legal(game, cursor, &[mv]);
+                    status = solver::advice::<G>(mv, verdict);
+                }
+            },
+            KeyCode::Char(':') => {
+                let Some(command) = read_line(game, ":")? else { continue; };
+                status = match record::run_command(game, players, &command) {
+                    Some(Ok(message)) => message,
+                    Some(Err(error)) => error.to_string(),
+                    None => format!("Unknown command: {}", command),
+                };
+            },
+            KeyCode::Char('q') | KeyCode::Esc => { return Ok(()); },
+            _ => { },
+        }
+    }
+}
+
+fn wait_for_key() -> io::Result<()> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press { return Ok(()); }
+        }
+    }
+}
+
+/// Reads a line typed under the board, or `None` if it was cancelled with Esc.
+fn read_line<G: Layout>(game: &G, prompt: &str) -> io::Result<Option<String>> {
+    let mut line = String::new();
+
+    loop {
+        draw(game, None, &format!("{}{}", prompt, line))?;
+
+        let Event::Key(key) = event::read()? else { continue; };
+        if key.kind != KeyEventKind::Press { continue; }
+
+        match key.code {
+            KeyCode::Enter => { return Ok(Some(line)); },
+            KeyCode::Esc => { return Ok(None); },
+            KeyCode::Backspace => { line.pop(); },
+            KeyCode::Char(c) => line.push(c),
+            _ => { },
+        }
+    }
+}
+
+/// The legal cell closest to `cursor`, or `cursor` itself if there are none.
+fn nearest_legal<G: Layout>(game: &G, cursor: (usize, usize), legal: &[G::Move]) -> (usize, usize) {
+    let (rows, cols) = game.size();
+
+    return (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| (row, col)))
+        .filter(|&(row, col)| legal.contains(&game.to_move(row, col)))
+        .min_by_key(|&(row, col)| row.abs_diff(cursor.0) + col.abs_diff(cursor.1))
+        .unwrap_or(cursor);
+}
+
+/// Draws the grid with `status` underneath. Empty cells that can be played are
+/// dotted, the block play is held to is shaded when there's more than one, or
+/// else the block under the cursor, winning lines are picked out and claimed cells take their owner's colour.
+fn draw<G: Layout>(game: &G, cursor: Option<(usize, usize)>, status: &str) -> io::Result<()> {
+    let mut out = io::stdout();
+    let (rows, cols) = game.size();
+    let (block_rows, block_cols) = game.block();
+    let legal = game.legal_moves();
+    let winning = game.winning_lines().concat();
+    let blocks = rows / block_rows * (cols / block_cols) > 1;
+    let shaded = match game.focused_block() {
+        _ if !blocks || game.is_over() => None,
+        Some(block) => Some(block),
+        None => cursor.map(|(row, col)| (row / block_rows, col / block_cols)),
+    };
+
+    queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    queue!(out, Print("Arrows or hjkl to move, Enter to place, 'u' to undo, 'r' to redo, '?' for a hint, ':save <file>' or ':load <file>', 'q' to quit\r\n"))?;
+    if game.is_misere() { queue!(out, Print("Misère: completing a line loses!".with(Color::Magenta)))?; }
+    queue!(out, Print("\r\n\r\n"))?;
+
+    let border = |row: usize| -> String {
+        let line = if row.is_multiple_of(block_rows) { "=" } else { "-" };
+        return (0..cols).map(|col| format!("{}{}", if col.is_multiple_of(block_cols) { "+" } else { line }, line.repeat(3))).collect::<String>() + "+";
+    };
+
+    for row in 0..rows {
+        queue!(out, Print(border(row)), Print("\r\n"))?;
+
+        for col in 0..cols {
+            queue!(out, Print(if col.is_multiple_of(block_cols) { "|" } else { ":" }))?;
+
+            let piece = game.cell(row, col);
+            let text = match piece {
+                Piece::X => " X ".with(Color::Red).bold(),
+                Piece::O => " O ".with(Color::Green).bold(),
+                Piece::Empty if legal.contains(&game.to_move(row, col)) => " . ".with(Color::Yellow),
+                Piece::Empty => "   ".stylize(),
+            };
+
+            let under_cursor = cursor == Some((row, col));
+            let in_block = shaded == Some((row / block_rows, col / block_cols));
+
+            let text = if under_cursor {
+                text.on(Color::White)
+            } else if winning.contains(&game.to_move(row, col)) {
+                text.on(Color::Magenta)
+            } else if in_block {
+                text.on(Color::DarkGrey)
+            } else {
+                match game.owner(row, col) {
+                    Piece::X => text.on(Color::DarkRed),
+                    Piece::O => text.on(Color::DarkGreen),
+                    Piece::Empty => text,
+                }
+            };
+            queue!(out, Print(text))?;
+        }
+
+        queue!(out, Print("|\r\n"))?;
+    }
+
+    queue!(out, Print(border(0)), Print("\r\n\r\n"), Print(status), Print("\r\n"))?;
+    return out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Game;
+
+    /// Every cell maps to a different move, and every move at the start is a cell.
+    fn covers_every_move<G: Layout>(game: G) {
+        let (rows, cols) = game.size();
+        let cells: Vec<G::Move> = (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col))).map(|(row, col)| game.to_move(row, col)).collect();
+
+        for (i, mv) in cells.iter().enumerate() {
+            assert!(!cells[..i].contains(mv));
+        }
+        for mv in game.legal_moves() {
+            assert!(cells.contains(&mv));
+        }
+    }
+
+    #[test]
+    fn layouts_cover_the_board() {
+        covers_every_move(Board::new());
+        covers_every_move(UltimateBoard::new());
+        covers_every_move(CubeBoard::new());
+        covers_every_move(QubicBoard::new());
+        covers_every_move(MnkBoard::new(5, 4, 3).unwrap());
+    }
+
+    #[test]
+    fn cells_show_the_pieces_played() {
+        let mut board = UltimateBoard::new();
+        board.apply((3, 5)).unwrap();
+
+        assert_eq!(board.cell(4, 2), Piece::X);
+        assert_eq!(board.to_move(4, 2), (3, 5));
+        // The move sends O to the sub-board on the right of the middle row
+        assert_eq!(board.focused_block(), Some((1, 2)));
+    }
+}
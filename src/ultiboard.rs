@@ -1,44 +1,91 @@
 use std::fmt;
+use std::str::FromStr;
+use crate::board::{self, EndGame};
+use crate::game::Game;
 use crate::utils::Piece;
+use colored::*;
 
 // Constants
 
 // Starting at index 0
 pub const BOARD_LEN: usize = 8;
 pub const ROW_LEN: usize = 2;
-
-const WIN_STATES: [u16; 8] = [
-    0b1_1100_0000,
-    0b0_0011_1000,
-    0b0_0000_0111,
-    0b1_0010_0100,
-    0b0_1001_0010,
-    0b0_0100_1001,
-    0b1_0001_0001,
-    0b0_0101_0100,
+const SUB_BOARD: u128 = board::FULL as u128;
+
+/// Characters across and lines down each sub-board when printed.
+const BLOCK_WIDTH: usize = 23;
+const BLOCK_LINES: usize = 10;
+
+/// Drawn over a sub-board once it's won.
+const BIG_X: [&str; 9] = [
+    "   \\               /   ",
+    "     \\           /     ",
+    "       \\       /       ",
+    "         \\   /         ",
+    "           X           ",
+    "         /   \\         ",
+    "       /       \\       ",
+    "     /           \\     ",
+    "   /               \\   ",
+];
+const BIG_O: [&str; 9] = [
+    "         _____         ",
+    "      .'       '.      ",
+    "     /           \\     ",
+    "    |             |    ",
+    "    |             |    ",
+    "    |             |    ",
+    "     \\           /     ",
+    "      '._______.'      ",
+    "                       ",
 ];
 
 // Type Definitions
 
+#[derive(Clone)]
 pub struct UltimateBoard {
-    boards: [Board; 9],
+    /// Each side's pieces, with bit `board * 9 + space` standing for that space.
+    xs: u128,
+    os: u128,
+    states: [BoardState; 9],
     active_board: BoardSelection,
     turn: Piece,
+    history: Vec<Snapshot>,
+    undone: Vec<(usize, usize)>,
+    rules: Rules,
+    /// Completing a line of sub-boards loses instead of wins.
+    misere: bool,
+}
+
+/// House rules that vary between tables. The default is the common set: drawn
+/// sub-boards count for no one, a free choice is between unfinished boards and
+/// a drawn meta-board is a cat's game.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rules {
+    /// Drawn sub-boards count towards a line for both players.
+    pub draws_count_for_both: bool,
+    /// A free choice may also pick a won sub-board with spaces left on it.
+    pub choose_any_board: bool,
+    /// A drawn meta-board goes to whoever won more sub-boards.
+    pub majority_tiebreak: bool,
 }
 
-struct Board {
-    spaces: [Piece; 9],
-    active: bool,
+/// Everything a move can change, so `undo` can put it back.
+#[derive(Clone)]
+struct Snapshot {
+    mv: (usize, usize),
+    active_board: BoardSelection,
     state: BoardState,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BoardState {
     InPlay,
     Winner(Piece),
     CatsGame,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BoardSelection {
     Unselected,
     Selected(usize),
@@ -48,6 +95,9 @@ pub enum BoardSelection {
 pub enum UltiError {
     OutOfBoundsError,
     SpaceTakenError,
+    IllegalBoardError,
+    GameOverError,
+    UnknownRuleError(String),
 }
 
 pub type UltiResult<T> = Result<T, UltiError>;
@@ -59,112 +109,79 @@ impl fmt::Display for UltiError {
         match self {
             UltiError::SpaceTakenError => write!(f, "There is already a piece there!"),
             UltiError::OutOfBoundsError => write!(f, "That space does not exist!"),
+            UltiError::IllegalBoardError => write!(f, "You can't play on that board!"),
+            UltiError::GameOverError => write!(f, "The game is already over!"),
+            UltiError::UnknownRuleError(rule) => write!(f, "Unknown rule '{}', expected 'standard', 'both', 'any' or 'majority'.", rule),
         }
     }
 }
 
-impl Board {
-    fn new() -> Self {
-        Self {
-            active: false,
-            state: BoardState::InPlay,
-            spaces: [ Piece::Empty; 9 ],
-        }
-    }
-
-    fn play(&mut self, space: usize, piece: Piece) -> UltiResult<()> {
-        if !self.active { panic!(); }
+impl std::error::Error for UltiError {}
 
-        match self.spaces[space] {
-            Piece::Empty => self.spaces[space] = piece,
-            _ => return Err(UltiError::SpaceTakenError),
-        }
+impl FromStr for Rules {
+    type Err = UltiError;
 
-        return Ok(());
-    }
+    /// `standard`, or any of `both`, `any` and `majority` separated by commas.
+    fn from_str(input: &str) -> UltiResult<Self> {
+        let mut rules = Rules::default();
 
-    fn get_state(&self) -> BoardState { self.state }
-
-    fn win_check(&mut self) -> BoardState {
-        let mut xs: u16 = 0b0_0000_0000;
-        let mut os: u16 = 0b0_0000_0000;
-
-        for i in 0..self.spaces.len() {
-            xs <<= 1;
-            os <<= 1;
-            match self.spaces[i] {
-                Piece::X => { xs ^= 0b0_0000_0001; },
-                Piece::O => { os ^= 0b0_0000_0001; },
-                Piece::Empty => { },
+        for rule in input.split(',').map(str::trim) {
+            match rule.to_lowercase().as_str() {
+                "standard" | "" => { },
+                "both" => { rules.draws_count_for_both = true; },
+                "any" => { rules.choose_any_board = true; },
+                "majority" => { rules.majority_tiebreak = true; },
+                _ => { return Err(UltiError::UnknownRuleError(String::from(rule))); },
             }
-
-            //println!("X: 0b{:09b}", xs);
-            //println!("O: 0b{:09b}", os);
         }
 
-        for state in WIN_STATES {
-            if xs & state == state {
-                self.state = BoardState::Winner(Piece::X);
-
-                self.spaces = [Piece::X; 9];
-
-                return self.state;
-            }
-            if os & state == state {
-                self.state = BoardState::Winner(Piece::O);
-
-                self.spaces = [Piece::O; 9];
-
-                return self.state;
-            }
-        }
+        return Ok(rules);
+    }
+}
 
-        if xs | os == 0b1_1111_1111 {
-            self.state = BoardState::CatsGame;
-            return self.state;
-        }
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = [
+            (self.draws_count_for_both, "both"),
+            (self.choose_any_board, "any"),
+            (self.majority_tiebreak, "majority"),
+        ].into_iter().filter(|&(on, _)| on).map(|(_, name)| name).collect();
 
-        return BoardState::InPlay;
+        return if names.is_empty() { write!(f, "standard") } else { write!(f, "{}", names.join(",")) };
     }
 }
 
+impl Default for UltimateBoard {
+    fn default() -> Self { Self::new() }
+}
+
 impl UltimateBoard {
     pub fn new() -> Self {
         Self {
+            xs: 0,
+            os: 0,
+            states: [BoardState::InPlay; 9],
             active_board: BoardSelection::Unselected,
             turn: Piece::X,
-            boards: [
-                Board::new(),
-                Board::new(),
-                Board::new(),
-                Board::new(),
-                Board::new(),
-                Board::new(),
-                Board::new(),
-                Board::new(),
-                Board::new(),
-            ],
+            history: Vec::new(),
+            undone: Vec::new(),
+            rules: Rules::default(),
+            misere: false,
         }
     }
 
-    pub fn focus(&mut self, board_index: BoardSelection) -> UltiResult<()> {
-        if let BoardSelection::Selected(s) = self.active_board {
-            self.boards[s].active = false;
-        }
+    pub fn with_rules(rules: Rules) -> Self { Self { rules, ..Self::new() } }
 
+    pub fn rules(&self) -> Rules { self.rules }
+
+    pub fn focus(&mut self, board_index: BoardSelection) -> UltiResult<()> {
         if let BoardSelection::Selected(s) = board_index {
             if s > BOARD_LEN { return Err(UltiError::OutOfBoundsError); }
 
-            match self.boards[s].state {
-                BoardState::InPlay => {
-                    self.boards[s].active = true;
-                },
-                _ => {
-                    self.active_board = BoardSelection::Unselected;
-                    return Ok(());
-                },
+            if !self.can_choose(s) {
+                self.active_board = BoardSelection::Unselected;
+                return Ok(());
             }
-
         }
 
         self.active_board = board_index;
@@ -177,20 +194,136 @@ impl UltimateBoard {
     pub fn play(&mut self, space: usize) -> UltiResult<()> {
         if space > BOARD_LEN { return Err(UltiError::OutOfBoundsError); }
 
-        let index;
+        let index = match self.active_board {
+            BoardSelection::Unselected => { return Err(UltiError::IllegalBoardError); },
+            BoardSelection::Selected(s) => s,
+        };
 
-        match self.active_board {
-            BoardSelection::Unselected => { panic!("No board is active!"); },
-            BoardSelection::Selected(s) => {
-                index = s;
-            },
+        let bit = 1 << (index * 9 + space);
+        if (self.xs | self.os) & bit != 0 { return Err(UltiError::SpaceTakenError); }
+
+        match self.turn {
+            Piece::X => { self.xs |= bit; },
+            Piece::O => { self.os |= bit; },
+            Piece::Empty => { },
         }
 
-        return self.boards[index].play(space, self.turn);
+        return Ok(());
+    }
+
+    /// Whose piece is on `space` of sub-board `index`.
+    pub fn get_space(&self, index: usize, space: usize) -> Piece {
+        let (xs, os) = self.sub_board(index);
+        return board::piece_at(xs, os, space);
+    }
+
+    /// Each side's bits on sub-board `index`, laid out like `board::Board`'s.
+    fn sub_board(&self, index: usize) -> (u16, u16) {
+        let shift = index * 9;
+        return (((self.xs >> shift) & SUB_BOARD) as u16, ((self.os >> shift) & SUB_BOARD) as u16);
+    }
+
+    fn empty_spaces(&self, index: usize) -> impl Iterator<Item = usize> {
+        let (xs, os) = self.sub_board(index);
+        let empty = !(xs | os) & board::FULL;
+
+        return (0..=BOARD_LEN).filter(move |&i| empty & (1 << i) != 0);
+    }
+
+    /// Whether sub-board `index` may be picked with a free choice: any board
+    /// still in play, and with `choose_any_board` won ones with spaces left.
+    fn can_choose(&self, index: usize) -> bool {
+        return match self.states[index] {
+            BoardState::InPlay => true,
+            BoardState::Winner(_) => self.rules.choose_any_board && self.empty_spaces(index).next().is_some(),
+            BoardState::CatsGame => false,
+        };
+    }
+
+    /// Settles sub-board `index` once it's been won or filled. Who owns it is
+    /// kept in `states`, leaving the pieces on it as they were played.
+    fn check_board(&mut self, index: usize) -> BoardState {
+        let (xs, os) = self.sub_board(index);
+
+        self.states[index] = match board::win_check(xs, os) {
+            EndGame::Winner(piece) => BoardState::Winner(piece),
+            EndGame::CatsGame => BoardState::CatsGame,
+            EndGame::NotDone => BoardState::InPlay,
+        };
+
+        return self.states[index];
+    }
+
+    fn make_move(&mut self, mv: (usize, usize)) -> UltiResult<()> {
+        let (b, space) = mv;
+
+        if b > BOARD_LEN || space > BOARD_LEN { return Err(UltiError::OutOfBoundsError); }
+        if self.is_over() { return Err(UltiError::GameOverError); }
+        if let BoardSelection::Selected(s) = self.active_board {
+            if s != b { return Err(UltiError::IllegalBoardError); }
+        }
+        if !self.can_choose(b) { return Err(UltiError::IllegalBoardError); }
+
+        let previous = self.active_board;
+        let snapshot = Snapshot {
+            mv,
+            active_board: self.sent_to(),
+            state: self.states[b],
+        };
+
+        self.focus(BoardSelection::Selected(b))?;
+        if let Err(error) = self.play(space) {
+            self.focus(previous)?;
+            return Err(error);
+        }
+
+        // A board that's already settled keeps its owner whatever is played on it
+        if let BoardState::InPlay = self.states[b] { self.check_board(b); }
+
+        // Being sent to a finished board gives a free choice
+        self.active_board = match self.states[space] {
+            BoardState::InPlay => BoardSelection::Selected(space),
+            _ => BoardSelection::Unselected,
+        };
+        self.next_turn();
+        self.history.push(snapshot);
+
+        return Ok(());
+    }
+
+    /// The board the last move sent the side to move to. Unlike `active_board`
+    /// this ignores a board picked by hand while having free choice.
+    fn sent_to(&self) -> BoardSelection {
+        if let Some(last) = self.history.last() {
+            let (_, space) = last.mv;
+            if let BoardState::InPlay = self.states[space] {
+                return BoardSelection::Selected(space);
+            }
+        }
+
+        return BoardSelection::Unselected;
     }
 
     pub fn get_turn(&self) -> Piece { self.turn }
 
+    pub fn is_misere(&self) -> bool { self.misere }
+
+    pub fn set_misere(&mut self, misere: bool) { self.misere = misere; }
+
+    /// Every (board, space) the side to move may play: only the focused board
+    /// if there is one, otherwise any board that can be chosen.
+    pub fn legal_moves(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let boards = match (self.meta_state(), self.active_board) {
+            (BoardState::InPlay, BoardSelection::Selected(s)) => s..s + 1,
+            (BoardState::InPlay, BoardSelection::Unselected) => 0..BOARD_LEN + 1,
+            _ => 0..0,
+        };
+
+        return boards
+            .filter(move |&b| self.can_choose(b))
+            .flat_map(move |b| self.empty_spaces(b).map(move |space| (b, space)));
+    }
+
     pub fn next_turn(&mut self) {
         self.turn = match self.turn {
             Piece::X => Piece::O,
@@ -200,173 +333,373 @@ impl UltimateBoard {
     }
 
     pub fn get_board_state(&self, index: usize) -> BoardState {
-        return self.boards[index].get_state();
+        return self.states[index];
     }
 
     pub fn win_check(&mut self) -> BoardState {
-        //let mut count = 0;
-        for board in &mut self.boards {
-            if let BoardState::InPlay = board.state {
-                //println!("checking board {}", count);
-                board.win_check();
+        for index in 0..=BOARD_LEN {
+            if let BoardState::InPlay = self.states[index] {
+                self.check_board(index);
             }
-            //count += 1;
         }
 
         if let BoardSelection::Selected(index) = self.active_board {
-            match self.boards[index].state {
-                BoardState::InPlay => { },
-                _ => {
-                    self.focus(BoardSelection::Unselected).unwrap();
-                },
+            if !self.can_choose(index) {
+                self.focus(BoardSelection::Unselected).unwrap();
+            }
+        }
+
+        return self.meta_state();
+    }
+
+    fn meta_state(&self) -> BoardState {
+        return match self.line_state() {
+            BoardState::Winner(piece) if self.misere => BoardState::Winner(piece.opponent()),
+            state => state,
+        };
+    }
+
+    /// Who has a line of sub-boards, or the majority under `majority_tiebreak`,
+    /// whatever that means for the result.
+    fn line_state(&self) -> BoardState {
+        // The side that just moved comes first, in case drawing a board finished a line for both
+        let mover = self.turn.opponent();
+        for piece in [mover, mover.opponent()] {
+            if self.meta_lines(piece).next().is_some() { return BoardState::Winner(piece); }
+        }
+
+        // Nothing left to play, even if some sub-boards were cat's games
+        if self.states.iter().all(|state| !matches!(state, BoardState::InPlay)) {
+            let xs = self.boards(BoardState::Winner(Piece::X)).count_ones();
+            let os = self.boards(BoardState::Winner(Piece::O)).count_ones();
+
+            if self.rules.majority_tiebreak && xs != os {
+                return BoardState::Winner(if xs > os { Piece::X } else { Piece::O });
             }
+            return BoardState::CatsGame;
         }
 
-        let mut xg: u16 = 0b0_0000_0000;
-        let mut og: u16 = 0b0_0000_0000;
-
-        for i in 0..self.boards.len() {
-            xg <<= 1;
-            og <<= 1;
-            if let BoardState::Winner(piece) = self.boards[i].state {
-                match piece {
-                    Piece::X => { xg ^= 0b0_0000_0001; },
-                    Piece::O => { og ^= 0b0_0000_0001; },
-                    Piece::Empty => { },
+        return BoardState::InPlay;
+    }
+
+    /// The sub-boards in `state`, with bit `i` standing for board `i`.
+    fn boards(&self, state: BoardState) -> u16 {
+        return (0..=BOARD_LEN).filter(|&i| self.states[i] == state).fold(0, |bits, i| bits | 1 << i);
+    }
+
+    /// The lines of sub-boards `piece` has completed, counting drawn boards
+    /// under `draws_count_for_both`. A line of nothing but drawn boards is no one's.
+    fn meta_lines(&self, piece: Piece) -> impl Iterator<Item = u16> {
+        let won = self.boards(BoardState::Winner(piece));
+        let counted = if self.rules.draws_count_for_both { self.boards(BoardState::CatsGame) } else { 0 };
+
+        return board::WIN_STATES.into_iter().filter(move |&state| (won | counted) & state == state && won & state != 0);
+    }
+
+    /// The lines of sub-boards that decided the game, as the boards along them.
+    /// Empty if no one has a line, even when the majority tiebreak decided it.
+    pub fn winning_boards(&self) -> Vec<[usize; 3]> {
+        let BoardState::Winner(piece) = self.line_state() else { return Vec::new(); };
+
+        return self.meta_lines(piece)
+            .map(|state| {
+                let mut boards = (0..=BOARD_LEN).filter(|&b| state & (1 << b) != 0);
+                [0; 3].map(|_| boards.next().unwrap())
+            })
+            .collect();
+    }
+
+    /// Prints the board with the sub-boards that can be played on highlighted,
+    /// won ones covered by their winner, the winning line reversed, cat's games
+    /// dimmed and the last move in brackets.
+    pub fn print(&self) {
+        println!("{}", self.render());
+    }
+
+    pub fn render(&self) -> String {
+        let last = self.history.last().map(|snapshot| snapshot.mv);
+        let winning = self.winning_boards().concat();
+
+        let mut display = String::from("   ");
+        for col in 0..3 {
+            display.push_str(&format!(" {:_^BLOCK_WIDTH$}", col + 1));
+        }
+        display.push_str(" \n");
+
+        for row in 0..3 {
+            for line in 0..BLOCK_LINES {
+                let label = if line == BLOCK_LINES / 2 { (b'A' + row as u8) as char } else { '|' };
+                display.push_str(&format!("   {}", label));
+
+                for col in 0..3 {
+                    display.push_str(&self.render_line(row * 3 + col, line, last, &winning));
+                    display.push('|');
                 }
+                display.push('\n');
             }
+
+            display.push_str(&format!("   |{}\n", format!("{}|", "_".repeat(BLOCK_WIDTH)).repeat(3)));
         }
 
-        for state in WIN_STATES {
-            if xg & state == state { return BoardState::Winner(Piece::X); }
-            if og & state == state { return BoardState::Winner(Piece::O); }
+        return display;
+    }
+
+    /// Line `line` of sub-board `index`, inside the borders it shares with its neighbours.
+    fn render_line(&self, index: usize, line: usize, last: Option<(usize, usize)>, winning: &[usize]) -> String {
+        let state = self.states[index];
+        let target = match self.active_board {
+            BoardSelection::Unselected => self.can_choose(index),
+            BoardSelection::Selected(s) => s == index,
+        };
+
+        let style = |text: &str| -> ColoredString {
+            return match state {
+                BoardState::CatsGame => text.dimmed(),
+                BoardState::Winner(piece) => piece.paint(text).bold(),
+                _ if target => text.yellow().bold(),
+                _ => text.normal(),
+            };
+        };
+
+        // A won board that can still be played on shows its spaces, framed in the winner's colour
+        if let (BoardState::Winner(piece), false) = (state, target) {
+            if line == 0 { return " ".repeat(BLOCK_WIDTH); }
+
+            let art = if piece == Piece::X { BIG_X } else { BIG_O };
+            let art = piece.paint(art[line - 1]).bold();
+
+            // Boards on the winning line stand out from the other won ones
+            return if winning.contains(&index) { art.reversed().to_string() } else { art.to_string() };
         }
 
-        if xg | og == 0b1_1111_1111 { return BoardState::CatsGame; }
+        if line == 0 { return style("   _____ _____ _____   ").to_string(); }
+
+        let row = (line - 1) / 3;
+        return match (line - 1) % 3 {
+            0 => format!("  {}  ", style("|     |     |     |")),
+            2 => format!("  {}  ", style("|_____|_____|_____|")),
+            _ => {
+                let mut text = format!("  {}", style("|"));
+
+                for col in 0..3 {
+                    let space = row * 3 + col;
+                    let piece = self.get_space(index, space).to_colored_string();
+                    let piece = if state == BoardState::CatsGame { piece.dimmed() } else { piece };
+
+                    if last == Some((index, space)) {
+                        text.push_str(&format!(" [{}] ", piece));
+                    } else {
+                        text.push_str(&format!("  {}  ", piece));
+                    }
+                    text.push_str(&style("|").to_string());
+                }
 
-        return BoardState::InPlay;
+                text.push_str("  ");
+                text
+            },
+        };
     }
+}
 
-    pub fn print(&self) {
-        println!(
-            "
-             ___________1___________ ___________2___________ ___________3___________ 
-            |   _____ _____ _____   |   _____ _____ _____   |   _____ _____ _____   |
-            |  |     |     |     |  |  |     |     |     |  |  |     |     |     |  |
-            |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |
-            |  |_____|_____|_____|  |  |_____|_____|_____|  |  |_____|_____|_____|  |
-            |  |     |     |     |  |  |     |     |     |  |  |     |     |     |  |
-            A  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |
-            |  |_____|_____|_____|  |  |_____|_____|_____|  |  |_____|_____|_____|  |
-            |  |     |     |     |  |  |     |     |     |  |  |     |     |     |  |
-            |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |
-            |  |_____|_____|_____|  |  |_____|_____|_____|  |  |_____|_____|_____|  |
-            |_______________________|_______________________|_______________________|
-            |   _____ _____ _____   |   _____ _____ _____   |   _____ _____ _____   |
-            |  |     |     |     |  |  |     |     |     |  |  |     |     |     |  |
-            |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |
-            |  |_____|_____|_____|  |  |_____|_____|_____|  |  |_____|_____|_____|  |
-            |  |     |     |     |  |  |     |     |     |  |  |     |     |     |  |
-            B  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |
-            |  |_____|_____|_____|  |  |_____|_____|_____|  |  |_____|_____|_____|  |
-            |  |     |     |     |  |  |     |     |     |  |  |     |     |     |  |
-            |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |
-            |  |_____|_____|_____|  |  |_____|_____|_____|  |  |_____|_____|_____|  |
-            |_______________________|_______________________|_______________________|
-            |   _____ _____ _____   |   _____ _____ _____   |   _____ _____ _____   |
-            |  |     |     |     |  |  |     |     |     |  |  |     |     |     |  |
-            |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |
-            |  |_____|_____|_____|  |  |_____|_____|_____|  |  |_____|_____|_____|  |
-            |  |     |     |     |  |  |     |     |     |  |  |     |     |     |  |
-            C  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |
-            |  |_____|_____|_____|  |  |_____|_____|_____|  |  |_____|_____|_____|  |
-            |  |     |     |     |  |  |     |     |     |  |  |     |     |     |  |
-            |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |  |  {}  |  {}  |  {}  |  |
-            |  |_____|_____|_____|  |  |_____|_____|_____|  |  |_____|_____|_____|  |
-            |_______________________|_______________________|_______________________|
-            ",
-            self.boards[0].spaces[0].to_colored_string(),
-            self.boards[0].spaces[1].to_colored_string(),
-            self.boards[0].spaces[2].to_colored_string(),
-            self.boards[1].spaces[0].to_colored_string(),
-            self.boards[1].spaces[1].to_colored_string(),
-            self.boards[1].spaces[2].to_colored_string(),
-            self.boards[2].spaces[0].to_colored_string(),
-            self.boards[2].spaces[1].to_colored_string(),
-            self.boards[2].spaces[2].to_colored_string(),
-            self.boards[0].spaces[3].to_colored_string(),
-            self.boards[0].spaces[4].to_colored_string(),
-            self.boards[0].spaces[5].to_colored_string(),
-            self.boards[1].spaces[3].to_colored_string(),
-            self.boards[1].spaces[4].to_colored_string(),
-            self.boards[1].spaces[5].to_colored_string(),
-            self.boards[2].spaces[3].to_colored_string(),
-            self.boards[2].spaces[4].to_colored_string(),
-            self.boards[2].spaces[5].to_colored_string(),
-            self.boards[0].spaces[6].to_colored_string(),
-            self.boards[0].spaces[7].to_colored_string(),
-            self.boards[0].spaces[8].to_colored_string(),
-            self.boards[1].spaces[6].to_colored_string(),
-            self.boards[1].spaces[7].to_colored_string(),
-            self.boards[1].spaces[8].to_colored_string(),
-            self.boards[2].spaces[6].to_colored_string(),
-            self.boards[2].spaces[7].to_colored_string(),
-            self.boards[2].spaces[8].to_colored_string(),
-            self.boards[3].spaces[0].to_colored_string(),
-            self.boards[3].spaces[1].to_colored_string(),
-            self.boards[3].spaces[2].to_colored_string(),
-            self.boards[4].spaces[0].to_colored_string(),
-            self.boards[4].spaces[1].to_colored_string(),
-            self.boards[4].spaces[2].to_colored_string(),
-            self.boards[5].spaces[0].to_colored_string(),
-            self.boards[5].spaces[1].to_colored_string(),
-            self.boards[5].spaces[2].to_colored_string(),
-            self.boards[3].spaces[3].to_colored_string(),
-            self.boards[3].spaces[4].to_colored_string(),
-            self.boards[3].spaces[5].to_colored_string(),
-            self.boards[4].spaces[3].to_colored_string(),
-            self.boards[4].spaces[4].to_colored_string(),
-            self.boards[4].spaces[5].to_colored_string(),
-            self.boards[5].spaces[3].to_colored_string(),
-            self.boards[5].spaces[4].to_colored_string(),
-            self.boards[5].spaces[5].to_colored_string(),
-            self.boards[3].spaces[6].to_colored_string(),
-            self.boards[3].spaces[7].to_colored_string(),
-            self.boards[3].spaces[8].to_colored_string(),
-            self.boards[4].spaces[6].to_colored_string(),
-            self.boards[4].spaces[7].to_colored_string(),
-            self.boards[4].spaces[8].to_colored_string(),
-            self.boards[5].spaces[6].to_colored_string(),
-            self.boards[5].spaces[7].to_colored_string(),
-            self.boards[5].spaces[8].to_colored_string(),
-            self.boards[6].spaces[0].to_colored_string(),
-            self.boards[6].spaces[1].to_colored_string(),
-            self.boards[6].spaces[2].to_colored_string(),
-            self.boards[7].spaces[0].to_colored_string(),
-            self.boards[7].spaces[1].to_colored_string(),
-            self.boards[7].spaces[2].to_colored_string(),
-            self.boards[8].spaces[0].to_colored_string(),
-            self.boards[8].spaces[1].to_colored_string(),
-            self.boards[8].spaces[2].to_colored_string(),
-            self.boards[6].spaces[3].to_colored_string(),
-            self.boards[6].spaces[4].to_colored_string(),
-            self.boards[6].spaces[5].to_colored_string(),
-            self.boards[7].spaces[3].to_colored_string(),
-            self.boards[7].spaces[4].to_colored_string(),
-            self.boards[7].spaces[5].to_colored_string(),
-            self.boards[8].spaces[3].to_colored_string(),
-            self.boards[8].spaces[4].to_colored_string(),
-            self.boards[8].spaces[5].to_colored_string(),
-            self.boards[6].spaces[6].to_colored_string(),
-            self.boards[6].spaces[7].to_colored_string(),
-            self.boards[6].spaces[8].to_colored_string(),
-            self.boards[7].spaces[6].to_colored_string(),
-            self.boards[7].spaces[7].to_colored_string(),
-            self.boards[7].spaces[8].to_colored_string(),
-            self.boards[8].spaces[6].to_colored_string(),
-            self.boards[8].spaces[7].to_colored_string(),
-            self.boards[8].spaces[8].to_colored_string(),
-            );
+impl Game for UltimateBoard {
+    /// (board, space)
+    type Move = (usize, usize);
+    type Error = UltiError;
+
+    fn side_to_move(&self) -> Piece { self.turn }
+
+    fn legal_moves(&self) -> Vec<(usize, usize)> { self.legal_moves().collect() }
+
+    fn apply(&mut self, mv: (usize, usize)) -> UltiResult<()> {
+        self.make_move(mv)?;
+        self.undone.clear();
+
+        return Ok(());
+    }
+
+    fn undo(&mut self) -> Option<(usize, usize)> {
+        let snapshot = self.history.pop()?;
+        let (b, space) = snapshot.mv;
+        let bit: u128 = 1 << (b * 9 + space);
+
+        self.xs &= !bit;
+        self.os &= !bit;
+        self.states[b] = snapshot.state;
+        self.focus(snapshot.active_board).unwrap();
+        self.next_turn();
+        self.undone.push(snapshot.mv);
+
+        return Some(snapshot.mv);
+    }
+
+    fn redo(&mut self) -> Option<(usize, usize)> {
+        let mv = self.undone.pop()?;
+        // The classic loop may have picked another board since the undo
+        self.active_board = self.sent_to();
+        self.make_move(mv).unwrap();
+
+        return Some(mv);
+    }
+
+    fn history(&self) -> Vec<(usize, usize)> {
+        return self.history.iter().map(|snapshot| snapshot.mv).collect();
+    }
+
+    /// The cells of the lines that won each sub-board along the winning line
+    /// of sub-boards. Drawn boards counting towards the line add no cells.
+    fn winning_lines(&self) -> Vec<Vec<(usize, usize)>> {
+        return self.winning_boards()
+            .into_iter()
+            .map(|boards| {
+                boards.into_iter().flat_map(|b| {
+                    let (xs, os) = self.sub_board(b);
+                    board::winning_lines(xs, os).into_iter().flatten().map(move |space| (b, space))
+                }).collect()
+            })
+            .collect();
+    }
+
+    fn outcome(&self) -> EndGame {
+        return match self.meta_state() {
+            BoardState::InPlay => EndGame::NotDone,
+            BoardState::Winner(piece) => EndGame::Winner(piece),
+            BoardState::CatsGame => EndGame::CatsGame,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_restores_a_won_sub_board() {
+        let mut board = UltimateBoard::new();
+        for mv in [(4, 0), (0, 4), (4, 1), (1, 4), (4, 2)] {
+            board.apply(mv).unwrap();
+        }
+        assert_eq!(board.get_board_state(4), BoardState::Winner(Piece::X));
+
+        board.undo();
+        assert_eq!(board.get_board_state(4), BoardState::InPlay);
+        assert_eq!([0, 1, 2].map(|space| board.get_space(4, space)), [Piece::X, Piece::X, Piece::Empty]);
+        assert_eq!(*board.get_focus(), BoardSelection::Selected(4));
+        assert_eq!(board.get_turn(), Piece::X);
+    }
+
+    #[test]
+    fn redo_ignores_a_board_picked_after_undo() {
+        let mut board = UltimateBoard::new();
+        for mv in [(4, 0), (0, 4), (4, 1), (1, 4), (4, 2), (2, 4), (8, 8)] {
+            board.apply(mv).unwrap();
+        }
+
+        board.undo();
+        board.focus(BoardSelection::Selected(7)).unwrap();
+
+        assert_eq!(board.redo(), Some((8, 8)));
+        assert_eq!(board.get_space(8, 8), Piece::X);
+        assert_eq!(board.get_turn(), Piece::O);
+    }
+
+    #[test]
+    fn won_sub_boards_keep_their_pieces() {
+        let mut board = UltimateBoard::new();
+        for mv in [(4, 0), (0, 4), (4, 1), (1, 4), (4, 2)] {
+            board.apply(mv).unwrap();
+        }
+
+        assert_eq!(board.get_board_state(4), BoardState::Winner(Piece::X));
+        assert_eq!((0..9).map(|space| board.get_space(4, space)).collect::<Vec<_>>(), [
+            Piece::X, Piece::X, Piece::X,
+            Piece::Empty, Piece::Empty, Piece::Empty,
+            Piece::Empty, Piece::Empty, Piece::Empty,
+        ]);
+        assert!(board.legal_moves().all(|(b, _)| b != 4));
+    }
+
+    /// A board with the sub-boards settled as given, `.` in play and `-` drawn,
+    /// and O to move.
+    fn settled(rules: Rules, states: &str) -> UltimateBoard {
+        let mut board = UltimateBoard::with_rules(rules);
+        for (i, state) in states.chars().enumerate() {
+            board.states[i] = match state {
+                'X' => BoardState::Winner(Piece::X),
+                'O' => BoardState::Winner(Piece::O),
+                '-' => BoardState::CatsGame,
+                _ => BoardState::InPlay,
+            };
+        }
+        board.next_turn();
+
+        return board;
+    }
+
+    #[test]
+    fn no_moves_after_the_game_ends() {
+        let mut board = settled(Rules::default(), "XXX......");
+
+        assert!(board.is_over());
+        assert!(matches!(board.apply((3, 0)), Err(UltiError::GameOverError)));
+    }
+
+    #[test]
+    fn drawn_boards_can_count_for_both() {
+        let both = Rules { draws_count_for_both: true, ..Rules::default() };
+
+        assert_eq!(settled(Rules::default(), "XX-......").meta_state(), BoardState::InPlay);
+        assert_eq!(settled(both, "XX-......").meta_state(), BoardState::Winner(Piece::X));
+        assert_eq!(settled(both, "---......").meta_state(), BoardState::InPlay);
+
+        // X drew the board finishing both lines, so X gets the win
+        assert_eq!(settled(both, "XX-..O..O").meta_state(), BoardState::Winner(Piece::X));
+    }
+
+    #[test]
+    fn majority_breaks_a_drawn_meta_board() {
+        let majority = Rules { majority_tiebreak: true, ..Rules::default() };
+
+        assert_eq!(settled(Rules::default(), "XOXXOOOXX").meta_state(), BoardState::CatsGame);
+        assert_eq!(settled(majority, "XOXXOOOXX").meta_state(), BoardState::Winner(Piece::X));
+        assert_eq!(settled(majority, "XOXXOOOX-").meta_state(), BoardState::CatsGame);
+    }
+
+    #[test]
+    fn free_choice_can_include_won_boards() {
+        let any = Rules { choose_any_board: true, ..Rules::default() };
+        let moves = [(4, 0), (0, 4), (4, 1), (1, 4), (4, 2), (2, 4)];
+
+        let mut standard = UltimateBoard::new();
+        let mut board = UltimateBoard::with_rules(any);
+        for mv in moves {
+            standard.apply(mv).unwrap();
+            board.apply(mv).unwrap();
+        }
+
+        // Sent to the won board 4, so the choice is free
+        assert_eq!(*board.get_focus(), BoardSelection::Unselected);
+        assert!(standard.apply((4, 5)).is_err());
+        board.apply((4, 5)).unwrap();
+        assert_eq!(board.get_board_state(4), BoardState::Winner(Piece::X));
+        assert_eq!(*board.get_focus(), BoardSelection::Selected(5));
+    }
+
+    #[test]
+    fn render_marks_wins_and_the_last_move() {
+        colored::control::set_override(false);
+
+        let mut board = UltimateBoard::new();
+        for mv in [(4, 0), (0, 4), (4, 1), (1, 4), (4, 2), (2, 6)] {
+            board.apply(mv).unwrap();
+        }
+
+        let display = board.render();
+        let lines: Vec<&str> = display.lines().collect();
+        assert!(lines.iter().all(|line| line.chars().count() == lines[0].chars().count()));
+
+        // Board 4 is covered by the big X, and O's last move is bracketed on board 2
+        assert!(lines[1 + BLOCK_LINES + 1 + 5].contains("           X           "));
+        assert_eq!(lines.iter().filter(|line| line.contains("[O]")).count(), 1);
     }
 }
 
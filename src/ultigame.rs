@@ -1,4 +1,4 @@
-use crate::{ultiboard::{UltimateBoard, BoardSelection, self, BoardState}, input::get_input, utils::{LoopState, Piece}};
+use crate::{ultiboard::{UltimateBoard, BoardSelection, self, BoardState}, input::{command, get_input, pause}, utils::{LoopState, Piece}, game::Game, notation::Notation, player::Players};
 use std::fmt;
 use colored::*;
 
@@ -17,9 +17,10 @@ impl fmt::Display for GameError {
     }
 }
 
-pub fn run(board: &mut UltimateBoard) -> LoopState {
+/// Runs one turn, asking a human for a move or letting the computer make one.
+pub fn run(board: &mut UltimateBoard, players: &Players) -> LoopState {
     clearscr!();
-    println!("Welcome to {} Please input to make your move! {}", "Ultimate TicTacToe!".green().bold(), "'q' to quit".red());
+    println!("Welcome to {} Please input to make your move! {}", "Ultimate TicTacToe!".green().bold(), "'u' to undo, 'r' to redo, 'hint' for a suggestion, 'save <file>' / 'load <file>', 'q' to quit".red());
 
     match board.win_check() {
         BoardState::Winner(piece) => {
@@ -46,25 +47,33 @@ pub fn run(board: &mut UltimateBoard) -> LoopState {
 
     board.print();
 
+    if players.is_computer(board.get_turn()) {
+        println!("({}) {}", board.get_turn().to_colored_string(), "Thinking...".magenta());
+        return players.play_turn(board);
+    }
+
     match board.get_focus() {
         BoardSelection::Selected(index) => {
-            println!("({}) {} {}", board.get_turn().to_colored_string(), "Current board:".green(), usize_to_notation(index.clone()));
+            println!("({}) {} {}", board.get_turn().to_colored_string(), "Current board:".green(), usize_to_notation(*index));
         },
         BoardSelection::Unselected => {
             println!("({}) {}", board.get_turn().to_colored_string(), "Select a board to play in.".magenta());
             let input = get_input();
 
-            if input.to_lowercase().trim() == "q" { return LoopState::Exit; }
+            if let Some(state) = command(board, players, &input) { return state; }
+
+            // A whole move such as 'b2a1', as the hint gives, picks the board and plays
+            if let Some(mv) = UltimateBoard::parse_move(input.trim()) {
+                play(board, players, mv);
+                return LoopState::Continue;
+            }
 
             match notation_to_usize(&input) {
                 Ok(index) => {
                     board.focus(BoardSelection::Selected(index)).unwrap();
                 },
                 Err(error) => {
-                    println!("{} Press 'Enter' to continue.", error);
-
-                    #[allow(unused_variables)]
-                    let input = get_input();
+                    pause(error);
                 },
             }
             return LoopState::Continue;
@@ -73,45 +82,41 @@ pub fn run(board: &mut UltimateBoard) -> LoopState {
 
     let input = get_input();
 
-    if input.trim() == "q" { return LoopState::Exit; }
-
-    match notation_to_usize(&input) {
-        Ok(index) => {
-            if let Err(error) = board.play(index) {
-                println!("{} Press 'Enter' to continue.", error);
-
-                #[allow(unused_variables)]
-                let input = get_input();
-
-                return LoopState::Continue;
-            }
+    if let Some(state) = command(board, players, &input) { return state; }
 
-            if let BoardState::InPlay = board.get_board_state(index) {
-                board.focus(BoardSelection::Selected(index)).unwrap();
-            } else {
-                board.focus(BoardSelection::Unselected).unwrap();
-            }
-        }
-        Err(error) => {
-            println!("{} Press 'Enter' to continue.", error);
+    let current = match board.get_focus() {
+        BoardSelection::Selected(index) => *index,
+        BoardSelection::Unselected => return LoopState::Continue,
+    };
 
-            #[allow(unused_variables)]
-            let input = get_input();
+    // Either a space on the current board or a whole move, which has to be on it
+    let mv = match UltimateBoard::parse_move(input.trim()) {
+        Some(mv) => Ok(mv),
+        None => notation_to_usize(&input).map(|index| (current, index)),
+    };
 
-            return LoopState::Continue;
-        }
+    match mv {
+        Ok(mv) => play(board, players, mv),
+        Err(error) => pause(error),
     }
 
-    board.next_turn();
-
     return LoopState::Continue;
 }
 
+/// Plays a move typed in by a human, pausing on the error if it's illegal.
+fn play(board: &mut UltimateBoard, players: &Players, mv: (usize, usize)) {
+    let piece = board.side_to_move();
+    match board.apply(mv) {
+        Ok(()) => players.announce::<UltimateBoard>(piece, mv),
+        Err(error) => pause(error),
+    }
+}
+
 fn notation_to_usize(input: &str) -> GameResult<usize> {
     let input = input.to_lowercase();
     let mut it = input.chars();
-    let row = it.next().unwrap_or_else(|| 'z') as usize;
-    let col = it.next().unwrap_or_else(|| '9') as usize;
+    let row = it.next().unwrap_or('z') as usize;
+    let col = it.next().unwrap_or('9') as usize;
 
     if row < 'a' as usize || col < '1' as usize { return Err(GameError::InvalidInput); }
 
@@ -5,7 +5,7 @@ pub enum LoopState {
     Exit,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Piece {
     X,
     O,
@@ -13,11 +13,28 @@ pub enum Piece {
 }
 
 impl Piece {
+    pub fn opponent(&self) -> Piece {
+        match self {
+            Piece::X => Piece::O,
+            Piece::O => Piece::X,
+            Piece::Empty => Piece::Empty,
+        }
+    }
+
     pub fn to_colored_string(&self) -> ColoredString {
         match self {
-            Piece::X => "X".bright_red(),
-            Piece::O => "O".green(),
-            Piece::Empty => " ".normal(),
+            Piece::X => self.paint("X"),
+            Piece::O => self.paint("O"),
+            Piece::Empty => self.paint(" "),
+        }
+    }
+
+    /// `text` in this piece's colour.
+    pub fn paint(&self, text: &str) -> ColoredString {
+        match self {
+            Piece::X => text.bright_red(),
+            Piece::O => text.green(),
+            Piece::Empty => text.normal(),
         }
     }
 }
@@ -0,0 +1,259 @@
+//! Property tests playing random legal games on every board and checking the
+//! rules hold after each move.
+
+#![allow(clippy::needless_return)]
+
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseResult;
+use tictactoe::{
+    board::{Board, EndGame},
+    cubegame::{cubeboard::CubeBoard, qubicboard::QubicBoard},
+    game::Game,
+    mnkboard::MnkBoard,
+    symmetry::{self, Symmetric, Symmetry},
+    ultiboard::{BoardSelection, BoardState, UltimateBoard},
+    utils::Piece,
+};
+
+/// Plays each choice as an index into the legal moves until the game ends,
+/// checking `game` after every move. Afterwards no move may be applied.
+fn play_out<G: Game>(mut game: G, choices: &[usize], mut check: impl FnMut(&G) -> TestCaseResult) -> TestCaseResult {
+    check(&game)?;
+
+    for choice in choices {
+        let moves = game.legal_moves();
+        if moves.is_empty() { break; }
+
+        let side = game.side_to_move();
+        game.apply(moves[choice % moves.len()]).unwrap();
+        prop_assert_eq!(game.side_to_move(), side.opponent());
+        check(&game)?;
+    }
+
+    if game.is_over() {
+        prop_assert!(game.legal_moves().is_empty());
+
+        // Every move that was still open before the last one must now be refused
+        let mut before = game.clone();
+        let last = before.undo().unwrap();
+        for mv in before.legal_moves().into_iter().filter(|&mv| mv != last) {
+            prop_assert!(game.clone().apply(mv).is_err());
+        }
+    }
+
+    return Ok(());
+}
+
+/// The position reached by playing each choice as an index into the legal moves.
+fn played<G: Game>(mut game: G, choices: &[usize]) -> G {
+    for choice in choices {
+        let moves = game.legal_moves();
+        if moves.is_empty() { break; }
+
+        game.apply(moves[choice % moves.len()]).unwrap();
+    }
+
+    return game;
+}
+
+/// Scans every cell of a board with the given side lengths in every direction
+/// for `k` of the same piece in a row.
+fn line_winner(sides: &[usize], k: usize, get: impl Fn(&[usize]) -> Piece) -> Option<Piece> {
+    let dims = sides.len();
+    let cells: usize = sides.iter().product();
+
+    for start in 0..cells {
+        let mut origin = vec![0; dims];
+        let mut rest = start;
+        for d in (0..dims).rev() {
+            origin[d] = rest % sides[d];
+            rest /= sides[d];
+        }
+
+        let piece = get(&origin);
+        if piece == Piece::Empty { continue; }
+
+        for direction in 0..3usize.pow(dims as u32) {
+            let step: Vec<isize> = (0..dims).map(|d| (direction / 3usize.pow(d as u32) % 3) as isize - 1).collect();
+            if step.iter().all(|&s| s == 0) { continue; }
+
+            let cell = |i: usize| -> Option<Vec<usize>> {
+                (0..dims)
+                    .map(|d| {
+                        let at = origin[d] as isize + step[d] * i as isize;
+                        if at >= 0 && (at as usize) < sides[d] { Some(at as usize) } else { None }
+                    })
+                    .collect()
+            };
+
+            if (1..k).all(|i| cell(i).is_some_and(|at| get(&at) == piece)) { return Some(piece); }
+        }
+    }
+
+    return None;
+}
+
+/// What `win_check` should say, given the brute-force winner and whether the board is full.
+fn expected(winner: Option<Piece>, full: bool) -> EndGame {
+    return match (winner, full) {
+        (Some(piece), _) => EndGame::Winner(piece),
+        (None, true) => EndGame::CatsGame,
+        (None, false) => EndGame::NotDone,
+    };
+}
+
+/// There are winning lines exactly when someone has won, each of `k` cells
+/// holding the winner's pieces.
+fn lines_match<M: Copy>(lines: Vec<Vec<M>>, k: usize, winner: Option<Piece>, get: impl Fn(M) -> Piece) -> TestCaseResult {
+    prop_assert_eq!(lines.is_empty(), winner.is_none());
+
+    for line in lines {
+        prop_assert_eq!(line.len(), k);
+        prop_assert!(line.iter().all(|&cell| Some(get(cell)) == winner));
+    }
+    return Ok(());
+}
+
+/// X moves first, so X has as many pieces as O, or one more when it's O's turn.
+fn counts_alternate(pieces: impl Iterator<Item = Piece>, side: Piece) -> TestCaseResult {
+    let pieces: Vec<Piece> = pieces.collect();
+    let xs = pieces.iter().filter(|&&piece| piece == Piece::X).count();
+    let os = pieces.iter().filter(|&&piece| piece == Piece::O).count();
+
+    prop_assert_eq!(xs, os + if side == Piece::O { 1 } else { 0 });
+    return Ok(());
+}
+
+/// Every image of the position under a symmetry has the same outcome and the
+/// same canonical form, and the symmetry `canonical` reports really gets there.
+fn symmetries_agree<G: Symmetric>(game: &G) -> TestCaseResult {
+    let (canon, to_canon) = symmetry::canonical(game);
+    prop_assert_eq!(symmetry::transform(game, to_canon).position_key(), canon.position_key());
+
+    for symmetry in Symmetry::all::<G>() {
+        let image = symmetry::transform(game, symmetry);
+        prop_assert_eq!(image.outcome(), game.outcome());
+        prop_assert_eq!(symmetry::canonical(&image).0.position_key(), canon.position_key());
+        prop_assert_eq!(symmetry::transform(&image, symmetry.inverse::<G>()).position_key(), game.position_key());
+    }
+    return Ok(());
+}
+
+fn choices() -> impl Strategy<Value = Vec<usize>> {
+    return prop::collection::vec(any::<usize>(), 0..100);
+}
+
+proptest! {
+    #[test]
+    fn board_rules(choices in choices()) {
+        play_out(Board::new(), &choices, |board| {
+            counts_alternate((0..9).map(|i| board.get(i)), board.side_to_move())?;
+
+            let winner = line_winner(&[3, 3], 3, |at| board.get(at[0] * 3 + at[1]));
+            let full = (0..9).all(|i| board.get(i) != Piece::Empty);
+            prop_assert_eq!(board.win_check(), expected(winner, full));
+            lines_match(board.winning_lines(), 3, winner, |i| board.get(i))?;
+            return Ok(());
+        })?;
+    }
+
+    #[test]
+    fn mnk_rules(width in 1..8usize, height in 1..8usize, k in 1..8usize, choices in choices()) {
+        let Some(board) = MnkBoard::new(width, height, k) else { return Ok(()); };
+
+        play_out(board, &choices, |board| {
+            let cells = || (0..height).flat_map(move |row| (0..width).map(move |col| board.get(row, col)));
+            counts_alternate(cells(), board.side_to_move())?;
+
+            let winner = line_winner(&[height, width], k, |at| board.get(at[0], at[1]));
+            let full = cells().all(|piece| piece != Piece::Empty);
+            prop_assert_eq!(board.win_check(), expected(winner, full));
+            lines_match(board.winning_lines(), k, winner, |(row, col)| board.get(row, col))?;
+            return Ok(());
+        })?;
+    }
+
+    #[test]
+    fn cube_rules(choices in choices()) {
+        play_out(CubeBoard::new(), &choices, |board| {
+            let cells = || (0..27).map(|i| board.get(i / 9, i % 9));
+            counts_alternate(cells(), board.side_to_move())?;
+
+            let winner = line_winner(&[3, 3, 3], 3, |at| board.get(at[0], at[1] * 3 + at[2]));
+            let full = cells().all(|piece| piece != Piece::Empty);
+            prop_assert_eq!(board.win_check(), expected(winner, full));
+            lines_match(board.winning_lines(), 3, winner, |(layer, index)| board.get(layer, index))?;
+            return Ok(());
+        })?;
+    }
+
+    #[test]
+    fn qubic_rules(choices in choices()) {
+        play_out(QubicBoard::new(), &choices, |board| {
+            let cells = || (0..64).map(|i| board.get(i / 16, i % 16));
+            counts_alternate(cells(), board.side_to_move())?;
+
+            let winner = line_winner(&[4, 4, 4], 4, |at| board.get(at[0], at[1] * 4 + at[2]));
+            let full = cells().all(|piece| piece != Piece::Empty);
+            prop_assert_eq!(board.win_check(), expected(winner, full));
+            lines_match(board.winning_lines(), 4, winner, |(layer, index)| board.get(layer, index))?;
+            return Ok(());
+        })?;
+    }
+
+    #[test]
+    fn symmetric_positions(choices in choices()) {
+        symmetries_agree(&played(Board::new(), &choices))?;
+        symmetries_agree(&played(UltimateBoard::new(), &choices))?;
+        symmetries_agree(&played(CubeBoard::new(), &choices))?;
+    }
+
+    #[test]
+    fn ultimate_rules(choices in choices()) {
+        play_out(UltimateBoard::new(), &choices, |board| {
+            let mut board = board.clone();
+            let outcome = board.win_check();
+
+            for b in 0..9 {
+                let state = board.get_board_state(b);
+                let winner = line_winner(&[3, 3], 3, |at| board.get_space(b, at[0] * 3 + at[1]));
+                let full = (0..9).all(|space| board.get_space(b, space) != Piece::Empty);
+
+                let expected = match expected(winner, full) {
+                    EndGame::Winner(piece) => BoardState::Winner(piece),
+                    EndGame::CatsGame => BoardState::CatsGame,
+                    EndGame::NotDone => BoardState::InPlay,
+                };
+                prop_assert_eq!(state, expected);
+            }
+
+            let won_by = |b: usize| match board.get_board_state(b) {
+                BoardState::Winner(piece) => piece,
+                _ => Piece::Empty,
+            };
+            let winner = line_winner(&[3, 3], 3, |at| won_by(at[0] * 3 + at[1]));
+            let finished = (0..9).all(|b| board.get_board_state(b) != BoardState::InPlay);
+            let expected = match expected(winner, finished) {
+                EndGame::Winner(piece) => BoardState::Winner(piece),
+                EndGame::CatsGame => BoardState::CatsGame,
+                EndGame::NotDone => BoardState::InPlay,
+            };
+            prop_assert_eq!(outcome, expected);
+
+            // Every sub-board in a winning line was won by the winner, on a line of its own
+            lines_match(board.winning_boards().into_iter().map(Vec::from).collect(), 3, winner, won_by)?;
+            // A sub-board can be won with two lines at once, so a winning line has at least 9 cells
+            let lines = board.winning_lines();
+            prop_assert_eq!(lines.len(), board.winning_boards().len());
+            for line in lines {
+                prop_assert!(line.len() >= 9 && line.len() % 3 == 0);
+                prop_assert!(line.iter().all(|&(b, space)| Some(board.get_space(b, space)) == winner));
+            }
+
+            if let (BoardState::InPlay, BoardSelection::Selected(focus)) = (outcome, *board.get_focus()) {
+                prop_assert_eq!(board.get_board_state(focus), BoardState::InPlay);
+            }
+            return Ok(());
+        })?;
+    }
+}